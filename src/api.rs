@@ -0,0 +1,655 @@
+/// This module contains logic related to versioning of the
+/// Liveboat JSON API, every published document is tagged with
+/// `apiVersion` and described by JSON Schema saved in the build dir.
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::builders::archive::{
+    archive_dirname, build_archive, ArchivePagination, FeedWithArchive,
+};
+use crate::builders::search_index::{
+    SearchIndex, SEARCH_DIRNAME, SEARCH_DOCUMENTS_FILENAME,
+    SEARCH_MANIFEST_FILENAME,
+};
+use crate::builders::site::{SiteMeta, SITE_META_FILENAME};
+use crate::feed::{Feed, FeedList};
+use crate::opts::Options;
+
+/// Version of the JSON API, bumped on every breaking change
+/// to the layout of published documents.
+pub const API_VERSION: u32 = 1;
+/// Directory name used for storing JSON Schema files.
+pub const SCHEMA_DIRNAME: &str = "schema";
+/// JSON Schema dialect used by schema files.
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Document wrapper adding API version to serialized data.
+#[derive(Serialize)]
+pub struct Versioned<'a, T: Serialize + ?Sized> {
+    #[serde(rename = "apiVersion")]
+    api_version: u32,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+impl<'a, T: Serialize + ?Sized> Versioned<'a, T> {
+    /// Wrap document data, tagging it with current API version.
+    pub fn new(data: &'a T) -> Versioned<'a, T> {
+        return Versioned {
+            api_version: API_VERSION,
+            data,
+        };
+    }
+}
+
+/// Type of the document published as part of the JSON API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiDocument {
+    /// `feeds/feeds.json`
+    FeedList,
+    /// `feeds/<id>.json`
+    Feed,
+    /// `feeds/<id>_archive.json`
+    FeedArchive,
+    /// `feeds/<id>_archive/index.json`
+    ArchiveIndex,
+    /// `feeds/<id>_archive/<key>.json`
+    ArchivePage,
+    /// `search/index.json`
+    SearchManifest,
+    /// `search/documents.json`
+    SearchDocuments,
+    /// `search/<key>.json`
+    SearchShard,
+    /// `feeds/meta.json`
+    SiteMeta,
+}
+
+impl ApiDocument {
+    /// All the document types published by Liveboat.
+    pub const ALL: [ApiDocument; 9] = [
+        ApiDocument::FeedList,
+        ApiDocument::Feed,
+        ApiDocument::FeedArchive,
+        ApiDocument::ArchiveIndex,
+        ApiDocument::ArchivePage,
+        ApiDocument::SearchManifest,
+        ApiDocument::SearchDocuments,
+        ApiDocument::SearchShard,
+        ApiDocument::SiteMeta,
+    ];
+
+    /// Name of the document type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ApiDocument::FeedList => "feed-list",
+            ApiDocument::Feed => "feed",
+            ApiDocument::FeedArchive => "feed-archive",
+            ApiDocument::ArchiveIndex => "archive-index",
+            ApiDocument::ArchivePage => "archive-page",
+            ApiDocument::SearchManifest => "search-manifest",
+            ApiDocument::SearchDocuments => "search-documents",
+            ApiDocument::SearchShard => "search-shard",
+            ApiDocument::SiteMeta => "site-meta",
+        }
+    }
+
+    /// Path of the schema file relative to the build directory.
+    pub fn schema_path(&self) -> String {
+        return format!("{}/{}.schema.json", SCHEMA_DIRNAME, self.name());
+    }
+
+    /// Generate JSON Schema describing the document.
+    pub fn schema(&self) -> Value {
+        let (title, root) = match self {
+            ApiDocument::FeedList => (
+                "Liveboat feed list",
+                versioned_object(json!({
+                    "feeds": array(json!({"$ref": "#/$defs/feedCompact"})),
+                })),
+            ),
+            ApiDocument::Feed => {
+                let mut props = feed_properties();
+                props["archive"] = json!({"$ref": "#/$defs/archiveIndex"});
+                ("Liveboat feed", versioned_object(props))
+            }
+            ApiDocument::FeedArchive => {
+                ("Liveboat feed archive", versioned_object(feed_properties()))
+            }
+            ApiDocument::ArchiveIndex => (
+                "Liveboat feed archive index",
+                versioned_object(archive_index_properties()),
+            ),
+            ApiDocument::ArchivePage => (
+                "Liveboat feed archive page",
+                versioned_object(json!({
+                    "id": string(),
+                    "key": string(),
+                    "items": array(json!({"$ref": "#/$defs/feedItem"})),
+                })),
+            ),
+            ApiDocument::SearchManifest => (
+                "Liveboat search index manifest",
+                versioned_object(json!({
+                    "version": integer(),
+                    "documents": string(),
+                    "documentCount": integer(),
+                    "minTermLength": integer(),
+                    "stemmedLanguages": array(string()),
+                    "shards": {
+                        "type": "object",
+                        "additionalProperties": object(json!({
+                            "path": string(),
+                            "terms": integer(),
+                        })),
+                    },
+                })),
+            ),
+            ApiDocument::SearchDocuments => (
+                "Liveboat search index documents",
+                array(object(json!({
+                    "feedId": string(),
+                    "guid": integer(),
+                    "title": string(),
+                    "url": string(),
+                    "date": integer(),
+                    "language": nullable("string"),
+                }))),
+            ),
+            ApiDocument::SearchShard => (
+                "Liveboat search index shard",
+                json!({
+                    "type": "object",
+                    "additionalProperties": array(json!({
+                        "type": "array",
+                        "prefixItems": [integer(), integer()],
+                        "minItems": 2,
+                        "maxItems": 2,
+                    })),
+                }),
+            ),
+            ApiDocument::SiteMeta => (
+                "Liveboat site metadata",
+                versioned_object(json!({
+                    "title": string(),
+                    "siteUrl": string(),
+                    "sitePath": string(),
+                    "private": boolean(),
+                    "buildTime": integer(),
+                    "buildDate": string(),
+                    "generator": string(),
+                    "links": array(object(json!({
+                        "title": string(),
+                        "href": string(),
+                        "type": string(),
+                    }))),
+                    "skippedRequests": array(string()),
+                })),
+            ),
+        };
+        let mut schema = json!({
+            "$schema": SCHEMA_DIALECT,
+            "$id": self.schema_path(),
+            "title": title,
+        });
+        for (key, value) in root.as_object().unwrap() {
+            schema[key] = value.clone();
+        }
+        schema["$defs"] = definitions();
+        return schema;
+    }
+}
+
+/// Serialized API document along with its path relative
+/// to the build directory.
+pub struct PublishedDocument {
+    pub kind: ApiDocument,
+    pub path: String,
+    pub data: Value,
+}
+
+impl PublishedDocument {
+    /// Serialize document data, top level objects are tagged
+    /// with API version.
+    fn init<T: Serialize + ?Sized>(
+        kind: ApiDocument,
+        path: String,
+        data: &T,
+    ) -> Result<PublishedDocument> {
+        let data = match kind {
+            ApiDocument::SearchDocuments | ApiDocument::SearchShard => {
+                serde_json::to_value(data)?
+            }
+            _ => serde_json::to_value(Versioned::new(data))?,
+        };
+        return Ok(PublishedDocument { kind, path, data });
+    }
+}
+
+/// Generate list of all the feeds, saved in `feeds_dir`.
+pub fn feed_list_document(
+    feed_list: &FeedList,
+    feeds_dir: &str,
+) -> Result<PublishedDocument> {
+    return PublishedDocument::init(
+        ApiDocument::FeedList,
+        format!("{}/feeds.json", feeds_dir),
+        feed_list,
+    );
+}
+
+/// Generate documents published for single feed: truncated feed
+/// data, archive index and pages and (if enabled) single
+/// file archive.
+pub fn feed_documents(
+    feed: &Feed,
+    opts: &Options,
+    feeds_dir: &str,
+) -> Result<Vec<PublishedDocument>> {
+    let mut truncated = feed.clone();
+    truncated.truncate_items();
+    if !opts.include_content_in_truncated_feeds {
+        for item in truncated.items.iter_mut() {
+            item.set_content(String::new());
+        }
+    }
+    let archive =
+        build_archive(feed, ArchivePagination::from_opts(opts), feeds_dir);
+    let mut documents = vec![
+        PublishedDocument::init(
+            ApiDocument::Feed,
+            format!("{}/{}.json", feeds_dir, feed.id()),
+            &FeedWithArchive {
+                feed: &truncated,
+                archive: &archive.index,
+            },
+        )?,
+        PublishedDocument::init(
+            ApiDocument::ArchiveIndex,
+            archive.index.path.clone(),
+            &archive.index,
+        )?,
+    ];
+    for (page, data) in archive.index.pages.iter().zip(&archive.pages) {
+        documents.push(PublishedDocument::init(
+            ApiDocument::ArchivePage,
+            page.path.clone(),
+            data,
+        )?);
+    }
+    if opts.legacy_archive_files {
+        documents.push(PublishedDocument::init(
+            ApiDocument::FeedArchive,
+            format!("{}/{}.json", feeds_dir, archive_dirname(feed)),
+            feed,
+        )?);
+    }
+    return Ok(documents);
+}
+
+/// Generate search index manifest, documents and shards.
+pub fn search_documents(index: &SearchIndex) -> Result<Vec<PublishedDocument>> {
+    let mut documents = vec![
+        PublishedDocument::init(
+            ApiDocument::SearchManifest,
+            format!("{}/{}", SEARCH_DIRNAME, SEARCH_MANIFEST_FILENAME),
+            &index.manifest(),
+        )?,
+        PublishedDocument::init(
+            ApiDocument::SearchDocuments,
+            format!("{}/{}", SEARCH_DIRNAME, SEARCH_DOCUMENTS_FILENAME),
+            index.documents(),
+        )?,
+    ];
+    for (key, shard) in index.shards() {
+        documents.push(PublishedDocument::init(
+            ApiDocument::SearchShard,
+            SearchIndex::shard_path(key),
+            shard,
+        )?);
+    }
+    return Ok(documents);
+}
+
+/// Generate site metadata document, saved in `feeds_dir`.
+pub fn site_meta_document(
+    meta: &SiteMeta,
+    feeds_dir: &str,
+) -> Result<PublishedDocument> {
+    return PublishedDocument::init(
+        ApiDocument::SiteMeta,
+        format!("{}/{}", feeds_dir, SITE_META_FILENAME),
+        meta,
+    );
+}
+
+fn string() -> Value {
+    return json!({"type": "string"});
+}
+
+fn integer() -> Value {
+    return json!({"type": "integer"});
+}
+
+fn boolean() -> Value {
+    return json!({"type": "boolean"});
+}
+
+fn nullable(t: &str) -> Value {
+    return json!({"type": [t, "null"]});
+}
+
+fn array(items: Value) -> Value {
+    return json!({"type": "array", "items": items});
+}
+
+/// Object schema with all the properties required.
+fn object(properties: Value) -> Value {
+    let required: Vec<&String> =
+        properties.as_object().unwrap().keys().collect();
+    return json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    });
+}
+
+/// Object schema of the top level document tagged with API version.
+fn versioned_object(mut properties: Value) -> Value {
+    properties["apiVersion"] = json!({"const": API_VERSION});
+    return object(properties);
+}
+
+/// Properties of the serialized feed.
+fn feed_properties() -> Value {
+    return json!({
+        "id": string(),
+        "title": string(),
+        "displayTitle": string(),
+        "url": string(),
+        "feedLink": string(),
+        "isQuery": boolean(),
+        "isEmpty": boolean(),
+        "isHidden": boolean(),
+        "itemCount": integer(),
+        "items": array(json!({"$ref": "#/$defs/feedItem"})),
+        "tags": array(string()),
+    });
+}
+
+/// Properties of the feed archive index.
+fn archive_index_properties() -> Value {
+    return json!({
+        "id": string(),
+        "path": string(),
+        "pagination": {"enum": ["size", "month"]},
+        "itemCount": integer(),
+        "pages": array(object(json!({
+            "key": string(),
+            "path": string(),
+            "itemCount": integer(),
+            "from": integer(),
+            "to": integer(),
+        }))),
+    });
+}
+
+/// Definitions of the types shared between documents.
+fn definitions() -> Value {
+    return json!({
+        "feedCompact": object(json!({
+            "id": string(),
+            "title": string(),
+            "displayTitle": string(),
+            "url": string(),
+            "feedLink": string(),
+            "isHidden": boolean(),
+            "isQuery": boolean(),
+            "tags": array(string()),
+            "itemCount": integer(),
+        })),
+        "feedItem": object(json!({
+            "title": string(),
+            "url": string(),
+            "originalUrl": string(),
+            "date": integer(),
+            "author": string(),
+            "guid": integer(),
+            "unread": boolean(),
+            "content": string(),
+            "contentLength": integer(),
+            "contentSource": {"enum": ["scraped", "extracted", "feed"]},
+            "wordCount": integer(),
+            "readingTime": integer(),
+            "language": nullable("string"),
+            "excerpt": nullable("string"),
+            "leadImage": nullable("string"),
+            "extra": {"type": "object", "additionalProperties": string()},
+            "tags": array(string()),
+            "highlights": array(json!({"$ref": "#/$defs/highlight"})),
+            "flags": nullable("string"),
+            "enclosureUrl": nullable("string"),
+            "enclosureMime": nullable("string"),
+            "enclosureLength": nullable("integer"),
+            "commentsUrl": nullable("string"),
+            "score": nullable("integer"),
+            "commentCount": nullable("integer"),
+            "submitter": nullable("string"),
+            "alsoIn": array(json!({"$ref": "#/$defs/alsoIn"})),
+            "duplicateOf": nullable("integer"),
+        })),
+        "highlight": object(json!({
+            "name": nullable("string"),
+            "foreground": string(),
+            "background": string(),
+            "attributes": array(string()),
+        })),
+        "alsoIn": object(json!({
+            "feedId": string(),
+            "feedTitle": string(),
+            "url": string(),
+            "commentsUrl": nullable("string"),
+        })),
+        "archiveIndex": object(archive_index_properties()),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::feed_item::FeedItem;
+    use crate::highlight::Highlight;
+    use crate::template::{Context, SimpleContext};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn init_feed() -> Feed {
+        let mut f = Feed::init(
+            "https://blog.com/rss".to_string(),
+            "Blog".to_string(),
+            "https://blog.com".to_string(),
+        );
+        f.tags.push("dev".to_string());
+        let mut i1 = FeedItem::new(
+            "Rust release",
+            "https://blog.com/1",
+            "https://blog.com/rss",
+            "exaroth",
+            1733200000,
+            true,
+            "<p>New rust compiler</p>",
+            1,
+        );
+        i1.set_text("New rust compiler".to_string());
+        i1.set_excerpt("New rust compiler".to_string());
+        i1.set_comments_url("https://news.ycombinator.com/1".to_string());
+        i1.set_enc_url("https://blog.com/1.mp3".to_string());
+        i1.set_enc_mime("audio/mpeg".to_string());
+        i1.set_enc_length(1234);
+        i1.set_extra("source".to_string(), "hn".to_string());
+        i1.set_highlights(vec![Highlight {
+            name: Some("rust".to_string()),
+            foreground: "green".to_string(),
+            background: "default".to_string(),
+            attributes: vec!["bold".to_string()],
+        }]);
+        let i2 = FeedItem::new(
+            "Cooking",
+            "https://blog.com/2",
+            "https://blog.com/rss",
+            "",
+            1730000000,
+            false,
+            "",
+            2,
+        );
+        f.items.extend([i1, i2]);
+        return f;
+    }
+
+    /// Validate document against the schema, only the subset
+    /// of JSON Schema used by Liveboat schemas is supported.
+    fn validate(value: &Value, schema: &Value, root: &Value, path: &str) {
+        if let Some(r) = schema.get("$ref").and_then(|r| r.as_str()) {
+            let name = r.trim_start_matches("#/$defs/");
+            return validate(value, &root["$defs"][name], root, path);
+        }
+        if let Some(c) = schema.get("const") {
+            assert_eq!(c, value, "{}", path);
+        }
+        if let Some(e) = schema.get("enum").and_then(|e| e.as_array()) {
+            assert!(e.contains(value), "{}: {}", path, value);
+        }
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(t)) => {
+                t.iter().map(|t| t.as_str().unwrap()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let actual = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        assert!(
+            types.is_empty() || types.contains(&actual),
+            "{}: expected {:?}, got {}",
+            path,
+            types,
+            actual
+        );
+        if let Value::Object(obj) = value {
+            let props = schema.get("properties");
+            for key in schema["required"].as_array().into_iter().flatten() {
+                let key = key.as_str().unwrap();
+                assert!(obj.contains_key(key), "{}: missing {}", path, key);
+            }
+            for (key, v) in obj {
+                let path = format!("{}.{}", path, key);
+                match props.and_then(|p| p.get(key)) {
+                    Some(s) => validate(v, s, root, &path),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            panic!("{}: property not in schema", path)
+                        }
+                        Some(s @ Value::Object(_)) => {
+                            validate(v, s, root, &path)
+                        }
+                        _ => (),
+                    },
+                }
+            }
+        }
+        if let Value::Array(items) = value {
+            for (idx, v) in items.iter().enumerate() {
+                let path = format!("{}[{}]", path, idx);
+                match schema["prefixItems"].get(idx) {
+                    Some(s) => validate(v, s, root, &path),
+                    None => {
+                        if let Some(s) = schema.get("items") {
+                            validate(v, s, root, &path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Serialized documents of all types, generated from the
+    /// test feed the same way the builder does, first document
+    /// of each type is returned.
+    fn documents() -> Vec<(ApiDocument, Value)> {
+        let f = init_feed();
+        let mut opts = Options::default();
+        opts.archive_page_size = 1;
+        let index = SearchIndex::build(&vec![f.clone()], &opts);
+        let url_feeds = vec![Arc::new(RefCell::new(f.clone()))];
+        let query_feeds = Vec::new();
+        let settings = HashMap::new();
+        let ctx = SimpleContext::init(
+            &url_feeds,
+            &query_feeds,
+            &opts,
+            &settings,
+            String::from("1.0"),
+            vec!["https://example.com/article".to_string()],
+        );
+        let meta = SiteMeta::init(
+            &opts,
+            ctx.alternate_links(),
+            ctx.skipped_requests(),
+            1733974974,
+        );
+
+        let mut published = vec![feed_list_document(
+            &FeedList::from_vec(vec![f.clone()]),
+            "feeds",
+        )
+        .unwrap()];
+        published.extend(feed_documents(&f, &opts, "feeds").unwrap());
+        published.extend(search_documents(&index).unwrap());
+        published.push(site_meta_document(&meta, "feeds").unwrap());
+
+        let mut documents: Vec<(ApiDocument, Value)> = Vec::new();
+        for doc in ApiDocument::ALL {
+            let mut value = published
+                .iter()
+                .find(|d| d.kind == doc)
+                .map(|d| d.data.clone())
+                .unwrap();
+            if doc == ApiDocument::SiteMeta {
+                // Keep snapshot independent of the Liveboat version.
+                value["generator"] = json!("liveboat/<version>");
+            }
+            documents.push((doc, value));
+        }
+        return documents;
+    }
+
+    #[test]
+    fn test_documents_match_schemas() {
+        let documents = documents();
+        assert_eq!(ApiDocument::ALL.len(), documents.len());
+        for (doc, value) in documents {
+            let schema = doc.schema();
+            assert_eq!(doc.schema_path(), schema["$id"]);
+            validate(&value, &schema, &schema, doc.name());
+        }
+    }
+
+    #[test]
+    fn test_api_snapshots() {
+        for (doc, value) in documents() {
+            insta::assert_json_snapshot!(doc.name(), value);
+        }
+    }
+}
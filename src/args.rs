@@ -57,6 +57,10 @@ pub struct Args {
     /// Print verbose code execution info.
     #[arg(long, default_value_t = false)]
     pub debug: bool,
+    /// Build without network access, using only feed content
+    /// and previously cached article data.
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
     /// If set will use nightly channel for updates.
     #[arg(long, default_value_t = false)]
     pub use_nightly: bool,
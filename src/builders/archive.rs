@@ -0,0 +1,242 @@
+/// This module contains logic related to splitting feed archives
+/// into pages so that clients do not have to download all the
+/// articles of the feed at once.
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::Options;
+
+/// Filename of the archive index document.
+const ARCHIVE_INDEX_FILENAME: &str = "index";
+
+/// Method used for splitting archive into pages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchivePagination {
+    /// Pages containing fixed number of articles, newest first.
+    Size(usize),
+    /// Pages containing articles published in given month.
+    Month,
+}
+
+impl ArchivePagination {
+    /// Retrieve pagination method defined in options,
+    /// defaults to fixed size pages.
+    pub fn from_opts(opts: &Options) -> ArchivePagination {
+        match opts.archive_pagination.to_lowercase().as_str() {
+            "month" => ArchivePagination::Month,
+            _ => {
+                ArchivePagination::Size(opts.archive_page_size.max(1) as usize)
+            }
+        }
+    }
+
+    /// Name of the pagination method.
+    fn name(&self) -> &'static str {
+        match self {
+            ArchivePagination::Size(_) => "size",
+            ArchivePagination::Month => "month",
+        }
+    }
+}
+
+/// Single archive page reference.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivePage {
+    /// Page number or month (`YYYY-MM`) of the page.
+    pub key: String,
+    /// Path to the page document relative to the site url.
+    pub path: String,
+    pub item_count: usize,
+    /// Publication timestamp of the oldest article in the page.
+    pub from: i64,
+    /// Publication timestamp of the newest article in the page.
+    pub to: i64,
+}
+
+/// Index document listing all the archive pages of the feed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveIndex {
+    pub id: String,
+    /// Path to the index document relative to the site url.
+    pub path: String,
+    pub pagination: &'static str,
+    pub item_count: usize,
+    pub pages: Vec<ArchivePage>,
+}
+
+/// Contents of single archive page.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivePageData<'a> {
+    pub id: &'a String,
+    pub key: String,
+    pub items: Vec<&'a FeedItem>,
+}
+
+/// Feed serialized alongside links to its archive pages.
+#[derive(Serialize)]
+pub struct FeedWithArchive<'a> {
+    #[serde(flatten)]
+    pub feed: &'a Feed,
+    pub archive: &'a ArchiveIndex,
+}
+
+/// Feed archive split into pages.
+pub struct FeedArchive<'a> {
+    pub index: ArchiveIndex,
+    pub pages: Vec<ArchivePageData<'a>>,
+}
+
+/// Directory containing archive pages of the feed,
+/// relative to the feeds directory.
+pub fn archive_dirname(feed: &Feed) -> String {
+    return format!("{}_archive", feed.id());
+}
+
+/// Split all the feed articles into archive pages, `feeds_dir`
+/// denotes location of the feed data relative to the site url.
+pub fn build_archive<'a>(
+    feed: &'a Feed,
+    pagination: ArchivePagination,
+    feeds_dir: &str,
+) -> FeedArchive<'a> {
+    let mut groups: Vec<(String, Vec<&FeedItem>)> = Vec::new();
+    match pagination {
+        ArchivePagination::Size(size) => {
+            for (idx, chunk) in feed.items.chunks(size).enumerate() {
+                groups.push(((idx + 1).to_string(), chunk.iter().collect()));
+            }
+        }
+        ArchivePagination::Month => {
+            for item in &feed.items {
+                let key = DateTime::from_timestamp(item.date(), 0)
+                    .unwrap_or_default()
+                    .format("%Y-%m")
+                    .to_string();
+                match groups.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, items)) => items.push(item),
+                    None => groups.push((key, vec![item])),
+                }
+            }
+        }
+    }
+    let dir = format!("{}/{}", feeds_dir, archive_dirname(feed));
+    let mut index = ArchiveIndex {
+        id: feed.id().clone(),
+        path: format!("{}/{}.json", dir, ARCHIVE_INDEX_FILENAME),
+        pagination: pagination.name(),
+        item_count: feed.items.len(),
+        pages: Vec::new(),
+    };
+    let mut pages = Vec::new();
+    for (key, items) in groups {
+        index.pages.push(ArchivePage {
+            path: format!("{}/{}.json", dir, key),
+            item_count: items.len(),
+            from: items.iter().map(|i| i.date()).min().unwrap_or(0),
+            to: items.iter().map(|i| i.date()).max().unwrap_or(0),
+            key,
+        });
+        pages.push(items);
+    }
+    let pages = index
+        .pages
+        .iter()
+        .zip(pages)
+        .map(|(page, items)| ArchivePageData {
+            id: feed.id(),
+            key: page.key.clone(),
+            items,
+        })
+        .collect();
+    return FeedArchive { index, pages };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn init_feed() -> Feed {
+        let mut f = Feed::init(
+            "http://blog.com/rss".to_string(),
+            "Blog".to_string(),
+            "http://blog.com".to_string(),
+        );
+        // 2024-12-03, 2024-12-01, 2024-11-30
+        for (guid, date) in [(1, 1733200000), (2, 1733050000), (3, 1733000000)]
+        {
+            f.add_item(FeedItem::new(
+                "item", "", "", "", date, false, "", guid,
+            ));
+        }
+        f.sort_items();
+        return f;
+    }
+
+    #[test]
+    fn test_splitting_archive_into_pages() {
+        let f = init_feed();
+        let archive = build_archive(&f, ArchivePagination::Size(2), "feeds");
+        let id = f.id();
+        assert_eq!(
+            format!("feeds/{}_archive/index.json", id),
+            archive.index.path
+        );
+        assert_eq!("size", archive.index.pagination);
+        assert_eq!(3, archive.index.item_count);
+        assert_eq!(
+            vec![
+                ArchivePage {
+                    key: "1".to_string(),
+                    path: format!("feeds/{}_archive/1.json", id),
+                    item_count: 2,
+                    from: 1733050000,
+                    to: 1733200000,
+                },
+                ArchivePage {
+                    key: "2".to_string(),
+                    path: format!("feeds/{}_archive/2.json", id),
+                    item_count: 1,
+                    from: 1733000000,
+                    to: 1733000000,
+                },
+            ],
+            archive.index.pages
+        );
+        assert_eq!(3, archive.pages[1].items[0].guid());
+    }
+
+    #[test]
+    fn test_splitting_archive_by_month() {
+        let f = init_feed();
+        let archive = build_archive(&f, ArchivePagination::Month, "feeds");
+        let keys: Vec<&String> =
+            archive.index.pages.iter().map(|p| &p.key).collect();
+        assert_eq!(vec!["2024-12", "2024-11"], keys);
+        assert_eq!(2, archive.pages[0].items.len());
+
+        let mut opts = Options::default();
+        assert_eq!(
+            ArchivePagination::Size(200),
+            ArchivePagination::from_opts(&opts)
+        );
+        opts.archive_pagination = "month".to_string();
+        assert_eq!(
+            ArchivePagination::Month,
+            ArchivePagination::from_opts(&opts)
+        );
+
+        let value = serde_json::to_value(FeedWithArchive {
+            feed: &f,
+            archive: &archive.index,
+        })
+        .unwrap();
+        assert_eq!(f.id().as_str(), value["id"]);
+        assert_eq!("month", value["archive"]["pagination"]);
+    }
+}
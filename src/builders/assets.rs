@@ -0,0 +1,304 @@
+/// This module contains logic related to post processing of the
+/// build output, such as content hash based asset naming and
+/// precompression of the generated files for static hosting.
+use log::info;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+
+/// Filename of the file mapping original asset paths
+/// to fingerprinted ones.
+pub const ASSET_MANIFEST_FILENAME: &str = "asset-manifest.json";
+/// Extensions of the files which are precompressed.
+const PRECOMPRESSED_EXTENSIONS: [&str; 3] = ["html", "json", "xml"];
+/// Extensions of the precompressed file variants.
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "br"];
+/// Number of hash characters included in fingerprinted filenames.
+const FINGERPRINT_LENGTH: usize = 10;
+/// Brotli compression settings (quality, window size).
+const BROTLI_QUALITY: u32 = 11;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+
+/// Mapping of original asset paths to fingerprinted ones,
+/// both relative to the build directory.
+pub type AssetMap = BTreeMap<String, String>;
+
+/// Generate path of the file containing content hash,
+/// eg. `assets/index.js` -> `assets/index.1a2b3c4d5e.js`.
+pub fn fingerprint_path(path: &str, data: &[u8]) -> String {
+    let hash = format!("{:x}", Sha256::digest(data));
+    let hash = &hash[..FINGERPRINT_LENGTH];
+    let (dir, filename) = match path.rsplit_once('/') {
+        Some((dir, filename)) => (format!("{}/", dir), filename),
+        None => (String::new(), path),
+    };
+    return match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => {
+            format!("{}{}.{}.{}", dir, stem, hash, ext)
+        }
+        _ => format!("{}{}.{}", dir, filename, hash),
+    };
+}
+
+/// Create fingerprinted copies of the files located in `dir` and its
+/// subdirectories (relative to the build directory), originals are
+/// kept intact so that paths constructed at runtime remain valid.
+/// Fingerprinted copies left over from previous builds are removed.
+pub fn fingerprint_dir(
+    build_dir: &Path,
+    dir: &str,
+    assets: &mut AssetMap,
+) -> Result<()> {
+    let src_dir = build_dir.join(dir);
+    if !src_dir.is_dir() {
+        return Ok(());
+    }
+    let mut stale = Vec::new();
+    let mut current = Vec::new();
+    for entry in fs::read_dir(&src_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}/{}", dir, name);
+        if entry.file_type()?.is_dir() {
+            fingerprint_dir(build_dir, &path, assets)?;
+            continue;
+        }
+        if is_compressed(&name) {
+            continue;
+        }
+        if is_fingerprinted(&name) {
+            stale.push(path);
+            continue;
+        }
+        let data = fs::read(entry.path())?;
+        let hashed = fingerprint_path(&path, &data);
+        fs::copy(entry.path(), build_dir.join(&hashed))?;
+        current.push(hashed.clone());
+        assets.insert(path, hashed);
+    }
+    for path in stale.iter().filter(|p| !current.contains(p)) {
+        info!("Removing stale asset {}", path);
+        fs::remove_file(build_dir.join(path))?;
+        for ext in COMPRESSED_EXTENSIONS {
+            _ = fs::remove_file(build_dir.join(format!("{}.{}", path, ext)));
+        }
+    }
+    Ok(())
+}
+
+/// Whether or not filename contains content hash.
+fn is_fingerprinted(filename: &str) -> bool {
+    let parts: Vec<&str> = filename.split('.').collect();
+    return parts.len() > 2
+        && parts[parts.len() - 2].len() == FINGERPRINT_LENGTH
+        && parts[parts.len() - 2]
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase());
+}
+
+/// Whether or not file is precompressed variant of another file.
+fn is_compressed(filename: &str) -> bool {
+    return COMPRESSED_EXTENSIONS
+        .iter()
+        .any(|ext| filename.ends_with(&format!(".{}", ext)));
+}
+
+/// Whether or not compressed variants of the file are generated.
+fn is_precompressed_source(path: &Path) -> bool {
+    return path.extension().is_some_and(|e| {
+        PRECOMPRESSED_EXTENSIONS.contains(&&*e.to_string_lossy())
+    });
+}
+
+/// Replace references to fingerprinted assets in `src` and `href`
+/// attributes of the rendered page, query strings are preserved.
+pub fn rewrite_references(html: &str, assets: &AssetMap) -> String {
+    let ref_re =
+        Regex::new(r#"((?:src|href)\s*=\s*["'])(\./|/)?([^"'?#]+)"#).unwrap();
+    return ref_re
+        .replace_all(html, |caps: &Captures| match assets.get(&caps[3]) {
+            Some(hashed) => format!(
+                "{}{}{}",
+                &caps[1],
+                caps.get(2).map_or("", |m| m.as_str()),
+                hashed
+            ),
+            None => caps[0].to_string(),
+        })
+        .to_string();
+}
+
+/// Write gzip and brotli compressed variants of all the html, json
+/// and xml files in the directory, variants newer than the source
+/// file are kept, variants of removed files are deleted.
+pub fn precompress_dir(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            precompress_dir(&path)?;
+            continue;
+        }
+        if is_compressed(&name) {
+            let source = path.with_extension("");
+            if is_precompressed_source(&source) && !source.is_file() {
+                _ = fs::remove_file(&path);
+            }
+            continue;
+        }
+        if !is_precompressed_source(&path) {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        let mut data = None;
+        for ext in COMPRESSED_EXTENSIONS {
+            let out_path = dir.join(format!("{}.{}", name, ext));
+            let is_fresh = fs::metadata(&out_path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|m| m >= modified);
+            if is_fresh {
+                continue;
+            }
+            if data.is_none() {
+                info!("Precompressing {}", path.display());
+                data = Some(fs::read(&path)?);
+            }
+            let compressed = compress(data.as_ref().unwrap(), ext)?;
+            File::create(out_path)?.write_all(&compressed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Compress data using gzip (`gz`) or brotli (`br`) encoding.
+fn compress(data: &[u8], ext: &str) -> io::Result<Vec<u8>> {
+    if ext == "br" {
+        let mut out = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(
+                &mut out,
+                4096,
+                BROTLI_QUALITY,
+                BROTLI_WINDOW_SIZE,
+            );
+            writer.write_all(data)?;
+        }
+        return Ok(out);
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    return encoder.finish();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_fingerprinting_assets() {
+        let hashed = fingerprint_path("assets/index.js", b"console.log(1)");
+        assert!(hashed.starts_with("assets/index."));
+        assert!(hashed.ends_with(".js"));
+        assert!(is_fingerprinted(hashed.rsplit('/').next().unwrap()));
+        assert_ne!(hashed, fingerprint_path("assets/index.js", b""));
+        assert!(!is_fingerprinted("index.js"));
+        assert!(fingerprint_path("LICENSE", b"").starts_with("LICENSE."));
+
+        let mut assets = AssetMap::new();
+        assets.insert(
+            "assets/index.js".to_string(),
+            "assets/index.0123456789.js".to_string(),
+        );
+        assets.insert(
+            "assets/index.css".to_string(),
+            "assets/index.abcdef0123.css".to_string(),
+        );
+        let html = r#"<link href="./assets/index.css?tv=1.0">
+<script src="/assets/index.js"></script>
+<a href="assets/index.json">"#;
+        assert_eq!(
+            r#"<link href="./assets/index.abcdef0123.css?tv=1.0">
+<script src="/assets/index.0123456789.js"></script>
+<a href="assets/index.json">"#,
+            rewrite_references(html, &assets)
+        );
+    }
+
+    #[test]
+    fn test_fingerprinting_dir() {
+        let dir = std::env::temp_dir().join("liveboat_fingerprint_test");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("assets/fonts")).unwrap();
+        fs::write(dir.join("assets/index.js"), "v1").unwrap();
+        fs::write(dir.join("assets/fonts/font.woff2"), "").unwrap();
+        let mut assets = AssetMap::new();
+        fingerprint_dir(&dir, "assets", &mut assets).unwrap();
+        let v1 = assets.get("assets/index.js").unwrap().clone();
+        assert!(dir.join(&v1).is_file());
+        assert!(dir
+            .join(assets.get("assets/fonts/font.woff2").unwrap())
+            .is_file());
+        fs::write(dir.join(format!("{}.gz", v1)), "").unwrap();
+
+        // Copies of the previous asset versions are removed
+        // together with their compressed variants.
+        fs::write(dir.join("assets/index.js"), "v2").unwrap();
+        let mut assets = AssetMap::new();
+        fingerprint_dir(&dir, "assets", &mut assets).unwrap();
+        let v2 = assets.get("assets/index.js").unwrap();
+        assert_ne!(&v1, v2);
+        assert!(dir.join(v2).is_file());
+        assert!(!dir.join(&v1).exists());
+        assert!(!dir.join(format!("{}.gz", v1)).exists());
+        assert!(dir.join("assets/index.js").is_file());
+        assert_eq!(2, assets.len());
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_precompressing_files() {
+        let dir = std::env::temp_dir().join("liveboat_precompress_test");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("feeds")).unwrap();
+        fs::write(dir.join("index.html"), "<html></html>").unwrap();
+        fs::write(dir.join("feeds/feed.json"), "{}").unwrap();
+        fs::write(dir.join("logo.png"), "").unwrap();
+        fs::write(dir.join("removed.json.gz"), "").unwrap();
+        fs::write(dir.join("archive.tar.gz"), "").unwrap();
+        fs::write(dir.join("font.woff2.br"), "").unwrap();
+        precompress_dir(&dir).unwrap();
+
+        let mut html = String::new();
+        GzDecoder::new(File::open(dir.join("index.html.gz")).unwrap())
+            .read_to_string(&mut html)
+            .unwrap();
+        assert_eq!("<html></html>", html);
+        let mut json = String::new();
+        brotli::Decompressor::new(
+            File::open(dir.join("feeds/feed.json.br")).unwrap(),
+            4096,
+        )
+        .read_to_string(&mut json)
+        .unwrap();
+        assert_eq!("{}", json);
+        assert!(!dir.join("logo.png.gz").exists());
+        assert!(!dir.join("removed.json.gz").exists());
+        assert!(dir.join("archive.tar.gz").is_file());
+        assert!(dir.join("font.woff2.br").is_file());
+        _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,248 @@
+/// This module contains logic related to generating JSON Feed 1.1
+/// documents (https://jsonfeed.org/version/1.1).
+use log::info;
+
+use anyhow::Result;
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::builders::utils::get_channel_items;
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::Options;
+
+/// Version identifier of JSON Feed documents.
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+/// Mime type used for attachments with unknown type.
+const DEFAULT_ATTACHMENT_MIME: &str = "application/octet-stream";
+
+/// Top level JSON Feed document.
+#[derive(Serialize, Debug)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    items: Vec<JsonFeedItem>,
+}
+
+/// Single JSON Feed item.
+#[derive(Serialize, Debug)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_url: Option<String>,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    date_published: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+/// Author of the JSON Feed item.
+#[derive(Serialize, Debug)]
+struct JsonFeedAuthor {
+    name: String,
+}
+
+/// Media associated with JSON Feed item.
+#[derive(Serialize, Debug)]
+struct JsonFeedAttachment {
+    url: String,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_in_bytes: Option<u64>,
+}
+
+impl From<FeedItem> for JsonFeedItem {
+    /// Articles linking to discussion (eg. aggregator submissions)
+    /// use the discussion page as item url and the scraped
+    /// article link as an external url.
+    fn from(item: FeedItem) -> JsonFeedItem {
+        let (url, external_url) = match item.comments_url() {
+            Some(c) => (c.clone(), Some(item.url().clone())),
+            None => (item.url().clone(), None),
+        };
+        let (content_html, content_text) = match item.content().is_empty() {
+            false => (Some(item.content().clone()), None),
+            true => (None, Some(item.text().clone().unwrap_or_default())),
+        };
+        let authors = match item.author().is_empty() {
+            true => Vec::new(),
+            false => vec![JsonFeedAuthor {
+                name: item.author().clone(),
+            }],
+        };
+        let attachments = match item.enc_url() {
+            Some(enc_url) if !enc_url.is_empty() => vec![JsonFeedAttachment {
+                url: enc_url.clone(),
+                mime_type: item
+                    .enc_mime()
+                    .clone()
+                    .filter(|m| !m.is_empty())
+                    .unwrap_or(DEFAULT_ATTACHMENT_MIME.to_string()),
+                size_in_bytes: item.enc_length(),
+            }],
+            _ => Vec::new(),
+        };
+        return JsonFeedItem {
+            id: item.stable_id(),
+            url,
+            external_url,
+            title: item.title().clone(),
+            content_html,
+            content_text,
+            summary: item.excerpt().clone(),
+            image: item.lead_image().clone(),
+            date_published: DateTime::from_timestamp(item.date(), 0)
+                .unwrap_or_default()
+                .to_rfc3339(),
+            authors,
+            tags: item.all_tags(),
+            attachments,
+        };
+    }
+}
+
+/// Generate JSON Feed document for the liveboat page, `path` denotes
+/// location of the document relative to the site url.
+pub fn generate_json_feed(
+    opts: &Options,
+    feeds: &Vec<Feed>,
+    general_channel: bool,
+    path: &str,
+    pretty: bool,
+) -> Result<String> {
+    info!("Generating json feed");
+    let (title, description) = match general_channel {
+        true => (opts.title.clone(), Some(String::from("Liveboat JSON Feed"))),
+        false => (feeds[0].display_title().clone(), None),
+    };
+    let mut items = Vec::new();
+    for item in get_channel_items(feeds) {
+        let mut json_item = JsonFeedItem::from(item);
+        if !opts.include_article_content_in_rss_feeds {
+            json_item.content_html = None;
+            json_item.content_text =
+                json_item.summary.clone().or(Some(String::new()));
+        }
+        items.push(json_item);
+    }
+    let feed = JsonFeed {
+        version: JSON_FEED_VERSION,
+        title,
+        home_page_url: opts.site_url.clone(),
+        feed_url: format!("{}/{}", opts.site_url.trim_end_matches('/'), path),
+        description,
+        items,
+    };
+    if pretty {
+        return Ok(serde_json::to_string_pretty(&feed)?);
+    }
+    return Ok(serde_json::to_string(&feed)?);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::{json, Value};
+    use std::cell::RefCell;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_generating_json_feed() {
+        let mut f = Feed::init(
+            "www.example.com/rss".to_string(),
+            "Test feed".to_string(),
+            "www.example.com".to_string(),
+        );
+        f.tags.push("dev".to_string());
+        let f = Arc::new(RefCell::new(f));
+        let mut i1 = FeedItem::new(
+            "item1",
+            "http://blog.com/post",
+            "",
+            "exaroth",
+            1733000000,
+            false,
+            "<p>Content</p>",
+            1,
+        );
+        i1.set_comments_url("https://news.ycombinator.com/1".to_string());
+        i1.set_enc_url("http://blog.com/a.mp3".to_string());
+        i1.set_excerpt("Content".to_string());
+        i1.set_ptr(Arc::clone(&f));
+        let mut i2 = FeedItem::new(
+            "item2",
+            "http://blog.com/2",
+            "",
+            "",
+            1733100000,
+            false,
+            "",
+            2,
+        );
+        i2.set_text("Text".to_string());
+        f.borrow_mut().items.extend([i1, i2]);
+
+        let result = generate_json_feed(
+            &Options::default(),
+            &vec![f.borrow().clone()],
+            false,
+            "channels/test.json",
+            false,
+        )
+        .unwrap();
+        let value: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            json!({
+                "version": "https://jsonfeed.org/version/1.1",
+                "title": "Test feed",
+                "home_page_url": "http://site-url-not-set.io/you-can-set-it-in-liveboat-config",
+                "feed_url": "http://site-url-not-set.io/you-can-set-it-in-liveboat-config/channels/test.json",
+                "items": [
+                    {
+                        "id": "http://blog.com/2",
+                        "url": "http://blog.com/2",
+                        "title": "item2",
+                        "content_text": "Text",
+                        "date_published": "2024-12-02T00:40:00+00:00",
+                    },
+                    {
+                        "id": "http://blog.com/post",
+                        "url": "https://news.ycombinator.com/1",
+                        "external_url": "http://blog.com/post",
+                        "title": "item1",
+                        "content_html": "<p>Content</p>",
+                        "summary": "Content",
+                        "date_published": "2024-11-30T20:53:20+00:00",
+                        "authors": [{"name": "exaroth"}],
+                        "tags": ["dev"],
+                        "attachments": [
+                            {
+                                "url": "http://blog.com/a.mp3",
+                                "mime_type": "application/octet-stream"
+                            }
+                        ]
+                    }
+                ]
+            }),
+            value
+        );
+    }
+}
@@ -3,7 +3,12 @@
 /// used for processing and outputting page files and
 /// rendering templates.
 ///
+pub mod archive;
+pub mod assets;
 pub mod aux;
+pub mod json_feed;
+pub mod search_index;
+pub mod site;
 pub mod spa_builder;
 
-mod utils;
+pub mod utils;
@@ -0,0 +1,333 @@
+/// This module contains logic related to generating static full text
+/// search index, allowing templates to implement search across all
+/// the articles without a server.
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::Options;
+
+/// Directory name used for storing search index files.
+pub const SEARCH_DIRNAME: &str = "search";
+/// Filename of the search index manifest.
+pub const SEARCH_MANIFEST_FILENAME: &str = "index.json";
+/// Filename of the list of indexed documents.
+pub const SEARCH_DOCUMENTS_FILENAME: &str = "documents.json";
+/// Version of the search index layout.
+const SEARCH_INDEX_VERSION: u32 = 1;
+/// Minimal number of characters for the term to be indexed.
+const MIN_TERM_LENGTH: usize = 2;
+
+/// Weights of the terms found in given article fields.
+const TITLE_WEIGHT: u32 = 4;
+const TAG_WEIGHT: u32 = 3;
+const AUTHOR_WEIGHT: u32 = 2;
+const TEXT_WEIGHT: u32 = 1;
+
+/// Article data returned as search result.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchDocument {
+    feed_id: String,
+    guid: i64,
+    title: String,
+    url: String,
+    date: i64,
+    language: Option<String>,
+}
+
+/// Reference to a single index shard.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchShardRef {
+    path: String,
+    terms: usize,
+}
+
+/// Manifest describing search index layout.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchManifest {
+    version: u32,
+    documents: String,
+    document_count: usize,
+    min_term_length: usize,
+    stemmed_languages: Vec<&'static str>,
+    shards: BTreeMap<String, SearchShardRef>,
+}
+
+/// Inverted index shard mapping terms to lists of
+/// `[document index, score]` pairs, sorted by score.
+pub type SearchShard = BTreeMap<String, Vec<(usize, u32)>>;
+
+/// Full text index of the articles, sharded by the
+/// first character of the term.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    documents: Vec<SearchDocument>,
+    shards: BTreeMap<String, SearchShard>,
+}
+
+/// ISO 639-3 language codes mapped to Snowball stemming algorithms.
+const STEMMER_LANGUAGES: [(&str, Algorithm); 17] = [
+    ("ara", Algorithm::Arabic),
+    ("dan", Algorithm::Danish),
+    ("deu", Algorithm::German),
+    ("ell", Algorithm::Greek),
+    ("eng", Algorithm::English),
+    ("fin", Algorithm::Finnish),
+    ("fra", Algorithm::French),
+    ("hun", Algorithm::Hungarian),
+    ("ita", Algorithm::Italian),
+    ("nld", Algorithm::Dutch),
+    ("nob", Algorithm::Norwegian),
+    ("por", Algorithm::Portuguese),
+    ("ron", Algorithm::Romanian),
+    ("rus", Algorithm::Russian),
+    ("spa", Algorithm::Spanish),
+    ("swe", Algorithm::Swedish),
+    ("tur", Algorithm::Turkish),
+];
+
+/// Retrieve stemmer for given language, if supported.
+fn get_stemmer(language: &Option<String>) -> Option<Stemmer> {
+    let language = language.as_ref()?;
+    return STEMMER_LANGUAGES
+        .iter()
+        .find(|(code, _)| code == language)
+        .map(|(_, algorithm)| Stemmer::create(*algorithm));
+}
+
+/// Split first `max_words` words of the text into lowercased terms,
+/// if stemmer is provided stem of the word is included as well so
+/// that both prefixes of the original word and its other inflected
+/// forms can be matched.
+pub fn tokenize(
+    text: &str,
+    stemmer: &Option<Stemmer>,
+    max_words: usize,
+) -> Vec<String> {
+    let mut terms = Vec::new();
+    let words = text
+        .unicode_words()
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.chars().count() >= MIN_TERM_LENGTH)
+        .take(max_words);
+    for word in words {
+        if let Some(s) = stemmer {
+            let stem = s.stem(&word).to_string();
+            if stem != word && stem.chars().count() >= MIN_TERM_LENGTH {
+                terms.push(stem);
+            }
+        }
+        terms.push(word);
+    }
+    return terms;
+}
+
+/// Retrieve key of the shard term should be stored in, non ascii
+/// characters are encoded as hex codepoints to keep filenames portable.
+pub fn shard_key(term: &str) -> String {
+    let c = term.chars().next().unwrap_or('_');
+    if c.is_ascii_alphanumeric() {
+        return c.to_string();
+    }
+    return format!("u{:x}", c as u32);
+}
+
+impl SearchIndex {
+    /// Build index of the articles of all the visible url feeds,
+    /// query feeds and articles marked as duplicates are skipped.
+    pub fn build(feeds: &Vec<Feed>, opts: &Options) -> SearchIndex {
+        info!("Generating search index");
+        let mut index = SearchIndex::default();
+        for f in feeds {
+            if f.is_query_feed() || f.is_hidden() {
+                continue;
+            }
+            for item in f.items.iter().filter(|i| i.duplicate_of().is_none()) {
+                index.add_document(
+                    f,
+                    item,
+                    opts.search_index_max_words as usize,
+                );
+            }
+        }
+        for shard in index.shards.values_mut() {
+            for postings in shard.values_mut() {
+                postings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            }
+        }
+        return index;
+    }
+
+    /// Index single article.
+    fn add_document(&mut self, f: &Feed, item: &FeedItem, max_words: usize) {
+        let doc_idx = self.documents.len();
+        let stemmer = get_stemmer(item.language());
+        let mut scores: HashMap<String, u32> = HashMap::new();
+        let mut add_terms = |text: &str, weight: u32, limit: usize| {
+            for term in tokenize(text, &stemmer, limit) {
+                *scores.entry(term).or_insert(0) += weight;
+            }
+        };
+        add_terms(item.title(), TITLE_WEIGHT, usize::MAX);
+        add_terms(item.author(), AUTHOR_WEIGHT, usize::MAX);
+        for tag in item.tags().iter().chain(f.tags.iter()) {
+            add_terms(tag, TAG_WEIGHT, usize::MAX);
+        }
+        if let Some(text) = item.text() {
+            add_terms(text, TEXT_WEIGHT, max_words);
+        }
+        for (term, score) in scores {
+            self.shards
+                .entry(shard_key(&term))
+                .or_default()
+                .entry(term)
+                .or_default()
+                .push((doc_idx, score));
+        }
+        self.documents.push(SearchDocument {
+            feed_id: f.id().clone(),
+            guid: item.guid(),
+            title: item.title().clone(),
+            url: item.url().clone(),
+            date: item.date(),
+            language: item.language().clone(),
+        });
+    }
+
+    /// Path of the shard relative to the build directory.
+    pub fn shard_path(key: &str) -> String {
+        return format!("{}/{}.json", SEARCH_DIRNAME, key);
+    }
+
+    /// Manifest listing documents and shards of the index.
+    pub fn manifest(&self) -> SearchManifest {
+        let shards = self
+            .shards
+            .iter()
+            .map(|(key, shard)| {
+                (
+                    key.clone(),
+                    SearchShardRef {
+                        path: SearchIndex::shard_path(key),
+                        terms: shard.len(),
+                    },
+                )
+            })
+            .collect();
+        return SearchManifest {
+            version: SEARCH_INDEX_VERSION,
+            documents: format!(
+                "{}/{}",
+                SEARCH_DIRNAME, SEARCH_DOCUMENTS_FILENAME
+            ),
+            document_count: self.documents.len(),
+            min_term_length: MIN_TERM_LENGTH,
+            stemmed_languages: STEMMER_LANGUAGES
+                .iter()
+                .map(|(code, _)| *code)
+                .collect(),
+            shards,
+        };
+    }
+
+    /// List of indexed documents, positions in the list
+    /// are referenced by shard postings.
+    pub fn documents(&self) -> &Vec<SearchDocument> {
+        return &self.documents;
+    }
+
+    /// Shards of the index keyed by the first term character.
+    pub fn shards(&self) -> &BTreeMap<String, SearchShard> {
+        return &self.shards;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::content::TextMetadata;
+
+    #[test]
+    fn test_tokenizing_text() {
+        assert_eq!(
+            vec!["running", "fast", "über", "rust's"],
+            tokenize("Running, fast! a Über-Rust's", &None, usize::MAX)
+        );
+        assert_eq!(vec!["running"], tokenize("Running, fast!", &None, 1));
+        let stemmer = get_stemmer(&Some("eng".to_string()));
+        assert_eq!(
+            vec!["run", "running", "librari", "libraries", "rust"],
+            tokenize("running libraries rust", &stemmer, usize::MAX)
+        );
+        assert!(get_stemmer(&Some("xxx".to_string())).is_none());
+        assert_eq!("r", shard_key("rust"));
+        assert_eq!("u44f", shard_key("яблоко"));
+    }
+
+    #[test]
+    fn test_building_search_index() {
+        let mut f = Feed::init(
+            "http://blog.com/rss".to_string(),
+            "Blog".to_string(),
+            "http://blog.com".to_string(),
+        );
+        f.tags.push("dev".to_string());
+        let mut i1 = FeedItem::new(
+            "Rust release",
+            "http://blog.com/1",
+            "",
+            "",
+            1733000000,
+            false,
+            "",
+            1,
+        );
+        i1.set_text("New rust compiler".to_string());
+        i1.set_text_metadata(TextMetadata {
+            word_count: 3,
+            reading_time: 1,
+            language: Some("eng".to_string()),
+        });
+        let mut i2 = FeedItem::new(
+            "Cooking",
+            "http://blog.com/2",
+            "",
+            "Rusty",
+            1733100000,
+            false,
+            "",
+            2,
+        );
+        i2.set_text("Pasta with rust colored sauce".to_string());
+        f.items.extend([i1, i2]);
+        let mut hidden = f.clone();
+        hidden.update_with_url_data(Vec::new(), true, None, 1);
+        let q_feed = Feed::init_query_feed("Query".to_string(), 2);
+
+        let index =
+            SearchIndex::build(&vec![f, hidden, q_feed], &Options::default());
+        assert_eq!(2, index.documents().len());
+        assert_eq!("Rust release", index.documents()[0].title);
+        let shard = &index.shards()["r"];
+        assert_eq!(&vec![(0, 5), (1, 1)], &shard["rust"]);
+        assert_eq!(&vec![(1, 2)], &shard["rusty"]);
+        assert_eq!(&vec![(0, 3), (1, 3)], &index.shards()["d"]["dev"]);
+        let shard = &index.shards()["c"];
+        assert_eq!(&vec![(0, 1)], &shard["compil"]);
+        assert_eq!(&vec![(0, 1)], &shard["compiler"]);
+
+        let manifest = index.manifest();
+        assert_eq!(2, manifest.document_count);
+        assert_eq!("search/r.json", manifest.shards["r"].path);
+        assert_eq!("search/documents.json", manifest.documents);
+    }
+}
@@ -0,0 +1,184 @@
+/// This module contains logic related to generating site level
+/// documents, such as `sitemap.xml`, `robots.txt` and site
+/// metadata used by templates and external tools.
+use chrono::{DateTime, SecondsFormat};
+use serde::Serialize;
+
+use crate::opts::Options;
+use crate::template::AlternateLink;
+
+/// Filename of the sitemap.
+pub const SITEMAP_FILENAME: &str = "sitemap.xml";
+/// Filename of the robots exclusion file.
+pub const ROBOTS_FILENAME: &str = "robots.txt";
+/// Filename of the site metadata document (saved in feeds dir).
+pub const SITE_META_FILENAME: &str = "meta.json";
+/// Sitemap protocol namespace.
+const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+/// Filename of the page served at the directory url.
+const DIRECTORY_INDEX_FILENAME: &str = "index.html";
+
+/// Site level metadata.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteMeta<'a> {
+    pub title: &'a String,
+    pub site_url: &'a String,
+    pub site_path: &'a String,
+    /// Whether or not the site should be excluded from indexing.
+    pub private: bool,
+    /// Timestamp of the build.
+    pub build_time: u64,
+    /// Build time in RFC 3339 format.
+    pub build_date: String,
+    /// Version of Liveboat used to generate the site.
+    pub generator: String,
+    /// Links to all the channels available for feed autodiscovery.
+    pub links: &'a Vec<AlternateLink>,
+    /// Urls of the network requests skipped in offline mode.
+    pub skipped_requests: &'a Vec<String>,
+}
+
+impl<'a> SiteMeta<'a> {
+    /// Collect site metadata from the options.
+    pub fn init(
+        opts: &'a Options,
+        links: &'a Vec<AlternateLink>,
+        skipped_requests: &'a Vec<String>,
+        build_time: u64,
+    ) -> SiteMeta<'a> {
+        return SiteMeta {
+            title: &opts.title,
+            site_url: &opts.site_url,
+            site_path: &opts.site_path,
+            private: opts.private_site,
+            build_time,
+            build_date: format_build_time(build_time),
+            generator: format!("liveboat/{}", env!("CARGO_PKG_VERSION")),
+            links,
+            skipped_requests,
+        };
+    }
+}
+
+/// Format build timestamp as RFC 3339 date.
+fn format_build_time(build_time: u64) -> String {
+    return DateTime::from_timestamp(build_time as i64, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+}
+
+/// Absolute url of the file located in the build dir.
+fn page_url(opts: &Options, path: &str) -> String {
+    let path = match path.strip_suffix(DIRECTORY_INDEX_FILENAME) {
+        Some(dir) => dir,
+        None => path,
+    };
+    return format!("{}/{}", opts.site_url.trim_end_matches('/'), path);
+}
+
+/// Escape special xml characters.
+fn escape_xml(raw: &str) -> String {
+    return raw
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;");
+}
+
+/// Generate sitemap listing given pages, `pages` contains paths
+/// relative to the build directory.
+pub fn generate_sitemap(
+    opts: &Options,
+    pages: &Vec<String>,
+    build_time: u64,
+) -> String {
+    let lastmod = format_build_time(build_time);
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"{}\">\n",
+        SITEMAP_NAMESPACE
+    );
+    for page in pages {
+        out.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+            escape_xml(&page_url(opts, page)),
+            lastmod
+        ));
+    }
+    out.push_str("</urlset>\n");
+    return out;
+}
+
+/// Generate robots exclusion file, private sites disallow
+/// crawling of all the paths.
+pub fn generate_robots_txt(opts: &Options) -> String {
+    let mut out = String::from("User-agent: *\n");
+    if opts.private_site {
+        out.push_str("Disallow: /\n");
+        return out;
+    }
+    if opts.robots_disallow.is_empty() {
+        out.push_str("Disallow:\n");
+    }
+    for path in &opts.robots_disallow {
+        out.push_str(&format!("Disallow: {}\n", path));
+    }
+    if opts.sitemap && opts.has_site_url() {
+        out.push_str(&format!(
+            "\nSitemap: {}\n",
+            page_url(opts, SITEMAP_FILENAME)
+        ));
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_generating_sitemap() {
+        let mut opts = Options::default();
+        opts.site_url = "https://news.example.com/".to_string();
+        let pages = vec!["index.html".to_string(), "a&b.html".to_string()];
+        assert_eq!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://news.example.com/</loc>
+    <lastmod>2024-12-12T03:42:54Z</lastmod>
+  </url>
+  <url>
+    <loc>https://news.example.com/a&amp;b.html</loc>
+    <lastmod>2024-12-12T03:42:54Z</lastmod>
+  </url>
+</urlset>
+"#,
+            generate_sitemap(&opts, &pages, 1733974974)
+        );
+    }
+
+    #[test]
+    fn test_generating_robots_txt() {
+        let mut opts = Options::default();
+        opts.site_url = "https://news.example.com".to_string();
+        assert_eq!(
+            "User-agent: *\nDisallow:\n\nSitemap: https://news.example.com/sitemap.xml\n",
+            generate_robots_txt(&opts)
+        );
+        opts.robots_disallow = vec!["/feeds/".to_string()];
+        opts.sitemap = false;
+        assert_eq!(
+            "User-agent: *\nDisallow: /feeds/\n",
+            generate_robots_txt(&opts)
+        );
+        opts.private_site = true;
+        assert_eq!("User-agent: *\nDisallow: /\n", generate_robots_txt(&opts));
+
+        let opts = Options::default();
+        assert!(!opts.has_site_url());
+        assert_eq!("User-agent: *\nDisallow:\n", generate_robots_txt(&opts));
+    }
+}
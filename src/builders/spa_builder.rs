@@ -1,4 +1,4 @@
-use log::info;
+use log::{info, warn};
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::Error as IOError;
@@ -10,8 +10,25 @@ use anyhow::Result;
 use handlebars::Handlebars;
 use url::Url;
 
+use crate::api::{
+    feed_documents, feed_list_document, search_documents, site_meta_document,
+    ApiDocument, PublishedDocument, SCHEMA_DIRNAME,
+};
+use crate::builders::assets::{
+    fingerprint_dir, precompress_dir, rewrite_references, AssetMap,
+    ASSET_MANIFEST_FILENAME,
+};
 use crate::builders::aux::Builder;
-use crate::builders::utils::{generate_opml, generate_rss_channel};
+use crate::builders::json_feed::generate_json_feed;
+use crate::builders::search_index::{SearchIndex, SEARCH_DIRNAME};
+use crate::builders::site::{
+    generate_robots_txt, generate_sitemap, SiteMeta, ROBOTS_FILENAME,
+    SITEMAP_FILENAME,
+};
+use crate::builders::utils::{
+    generate_atom_feed, generate_opml, generate_rss_channel, get_tag_feeds,
+    ChannelFormat,
+};
 use crate::errors::ConfigurationError;
 use crate::feed::{Feed, FeedList};
 use crate::template::Context;
@@ -32,6 +49,8 @@ const RSS_FILE_FILENAME: &str = "rss.xml";
 const SELF_REFERENTIAL_RSS_DIRNAME: &str = "channels";
 /// Filename of the ompl file.
 const OPML_FILENAME: &str = "opml.xml";
+/// Dirname of the directory containing template assets.
+const ASSETS_DIRNAME: &str = "assets";
 
 /// This represents default builder module
 /// used for processing single page Liveboat templates.
@@ -57,6 +76,8 @@ where
         info!("Creating tmp feeds dir");
         _ = fs::create_dir(self.tmp_dir.join(FEEDS_DIRNAME))?;
         _ = fs::create_dir(self.tmp_dir.join(SELF_REFERENTIAL_RSS_DIRNAME))?;
+        _ = fs::create_dir(self.tmp_dir.join(SEARCH_DIRNAME))?;
+        _ = fs::create_dir(self.tmp_dir.join(SCHEMA_DIRNAME))?;
         Ok(())
     }
 
@@ -66,7 +87,15 @@ where
         self.save_json_feeds()?;
         self.save_build_time()?;
         self.save_rss_channel()?;
-        self.save_query_feed_channels()?;
+        if self.context.options().atom_feeds {
+            self.save_atom_feed()?;
+        }
+        self.save_feed_channels()?;
+        if self.context.options().search_index {
+            self.save_search_index()?;
+        }
+        self.save_api_schemas()?;
+        self.save_site_meta()?;
         self.save_opml()?;
         Ok(())
     }
@@ -94,6 +123,20 @@ where
         let channel_dir = self.build_dir.join(SELF_REFERENTIAL_RSS_DIRNAME);
         copy_all(channel_dir_tmp, &channel_dir)?;
 
+        let schema_dir = self.build_dir.join(SCHEMA_DIRNAME);
+        if schema_dir.is_dir() {
+            _ = fs::remove_dir_all(&schema_dir);
+        }
+        copy_all(self.tmp_dir.join(SCHEMA_DIRNAME), &schema_dir)?;
+
+        let search_dir = self.build_dir.join(SEARCH_DIRNAME);
+        if search_dir.is_dir() {
+            _ = fs::remove_dir_all(&search_dir);
+        }
+        if self.context.options().search_index {
+            copy_all(self.tmp_dir.join(SEARCH_DIRNAME), &search_dir)?;
+        }
+
         let tpl_index_path =
             self.tmp_dir.join(format!("{}.html", INDEX_FILENAME));
         let index_path =
@@ -116,6 +159,32 @@ where
             self.tmp_dir.join(OPML_FILENAME),
             self.build_dir.join(OPML_FILENAME),
         )?;
+        if self.context.options().atom_feeds {
+            let atom_path = ChannelFormat::Atom.site_channel_path();
+            fs::copy(
+                self.tmp_dir.join(&atom_path),
+                self.build_dir.join(&atom_path),
+            )?;
+        }
+        if self.context.options().json_feeds {
+            let json_path = ChannelFormat::JsonFeed.site_channel_path();
+            fs::copy(
+                self.tmp_dir.join(&json_path),
+                self.build_dir.join(&json_path),
+            )?;
+        }
+        let manifest_path = self.build_dir.join(ASSET_MANIFEST_FILENAME);
+        if manifest_path.is_file() {
+            _ = fs::remove_file(&manifest_path);
+        }
+        if self.context.options().fingerprint_assets {
+            self.fingerprint_assets()?;
+        }
+        self.save_site_files()?;
+        if self.context.options().precompress_outputs {
+            info!("Precompressing build files");
+            precompress_dir(self.build_dir)?;
+        }
         Ok(())
     }
 
@@ -210,12 +279,35 @@ where
         Ok(())
     }
 
-    /// Save atom feed for all the feeds
+    /// Save Atom feed for all the feeds.
+    fn save_atom_feed(&self) -> Result<()> {
+        let path = ChannelFormat::Atom.site_channel_path();
+        let mut file = File::create(self.tmp_dir.join(&path))?;
+        file.write_all(
+            generate_atom_feed(
+                self.context.options(),
+                self.context.feeds(),
+                true,
+                &path,
+            )
+            .as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Save OPML file listing all the feeds.
     fn save_opml(&self) -> Result<()> {
         let url = Url::parse(self.context.options().site_url.as_str());
         if url.is_err() {
             return Err(ConfigurationError::InvalidSiteUrl.into());
         }
+        let format = &self.context.options().opml_channel_format;
+        if ChannelFormat::from_name(format).is_none() {
+            return Err(ConfigurationError::InvalidChannelFormat(
+                format.clone(),
+            )
+            .into());
+        }
         let path = self.tmp_dir.join(OPML_FILENAME);
         let mut file = File::create(path)?;
         file.write_all(
@@ -229,37 +321,51 @@ where
         Ok(())
     }
 
-    /// Save single feed data in tmp dir.
-    fn save_feed_data(&self, name: &String, data: &[u8]) -> Result<()> {
-        let feeds_dir = self.tmp_dir.join(FEEDS_DIRNAME);
-        let path = feeds_dir.join(format!("{}.json", name));
-        info!("Saving feed at path {}", path.display());
-        let mut file = File::create(path)?;
-        file.write_all(data)?;
-        Ok(())
-    }
-
     /// Save rss feeds containing query feed data used in the
-    /// self referential data used in OPML channel.
-    fn save_query_feed_channels(&self) -> Result<()> {
-        let base_path = self.tmp_dir.join(SELF_REFERENTIAL_RSS_DIRNAME);
-        let q_feeds: Vec<&Feed> = self
+    /// self referential data used in OPML channel, url feed and
+    /// tag channels are saved alongside if enabled.
+    fn save_feed_channels(&self) -> Result<()> {
+        let opts = self.context.options();
+        let publish_url_feeds =
+            opts.url_feed_channels || opts.opml_liveboat_channels;
+        let c_feeds: Vec<&Feed> = self
             .context
             .feeds()
             .into_iter()
-            .filter(|f| f.is_query_feed() == true)
+            .filter(|f| {
+                f.is_query_feed() || (publish_url_feeds && !f.is_hidden())
+            })
             .collect();
-        for f in q_feeds {
-            let path = base_path.join(format!("{}.xml", f.id()));
-            info!("Saving channel data for query feed: {}", path.display());
-            let mut file = File::create(path)?;
+        for f in c_feeds {
+            info!("Saving channel data for feed: {}", f.id());
+            self.save_feed_channel(f)?;
+        }
+        if !opts.tag_channels {
+            return Ok(());
+        }
+        for f in get_tag_feeds(self.context.feeds()) {
+            info!("Saving channel data for tag: {}", f.display_title());
+            self.save_feed_channel(&f)?;
+            if opts.json_feeds {
+                self.save_feed_json_feed(&f)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save RSS channel for single feed, Atom document is saved
+    /// alongside if enabled.
+    fn save_feed_channel(&self, f: &Feed) -> Result<()> {
+        let opts = self.context.options();
+        let feeds = Vec::from([f.clone()]);
+        let path = self.tmp_dir.join(ChannelFormat::Rss.feed_channel_path(f));
+        let mut file = File::create(path)?;
+        file.write_all(generate_rss_channel(opts, &feeds, false).as_bytes())?;
+        if opts.atom_feeds {
+            let atom_path = ChannelFormat::Atom.feed_channel_path(f);
+            let mut file = File::create(self.tmp_dir.join(&atom_path))?;
             file.write_all(
-                generate_rss_channel(
-                    self.context.options(),
-                    &Vec::from([f.clone()]),
-                    false,
-                )
-                .as_bytes(),
+                generate_atom_feed(opts, &feeds, false, &atom_path).as_bytes(),
             )?;
         }
         Ok(())
@@ -272,27 +378,170 @@ where
             if !f.is_empty() && !f.is_hidden() {
                 f_list.add_feed(&f);
                 self.save_json_feed(f)?;
+                if self.context.options().json_feeds {
+                    self.save_feed_json_feed(f)?;
+                }
             }
         }
-        self.save_json_feedlist(&f_list, String::from("feeds"))?;
+        self.save_document(&feed_list_document(&f_list, FEEDS_DIRNAME)?)?;
+        if self.context.options().json_feeds {
+            self.save_site_json_feed()?;
+        }
+        Ok(())
+    }
+
+    /// Save JSON Feed document containing all the feeds.
+    fn save_site_json_feed(&self) -> Result<()> {
+        let path = ChannelFormat::JsonFeed.site_channel_path();
+        let mut file = File::create(self.tmp_dir.join(&path))?;
+        file.write_all(
+            generate_json_feed(
+                self.context.options(),
+                self.context.feeds(),
+                true,
+                &path,
+                self.debug,
+            )?
+            .as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Save JSON Feed document for single feed.
+    fn save_feed_json_feed(&self, feed: &Feed) -> Result<()> {
+        let path = ChannelFormat::JsonFeed.feed_channel_path(feed);
+        info!("Saving json feed at path {}", path);
+        let mut file = File::create(self.tmp_dir.join(&path))?;
+        file.write_all(
+            generate_json_feed(
+                self.context.options(),
+                &Vec::from([feed.clone()]),
+                false,
+                &path,
+                self.debug,
+            )?
+            .as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Save search index manifest, documents and shards.
+    fn save_search_index(&self) -> Result<()> {
+        let index =
+            SearchIndex::build(self.context.feeds(), self.context.options());
+        for doc in search_documents(&index)? {
+            self.save_document(&doc)?;
+        }
+        Ok(())
+    }
+
+    /// Save API document in the tmp directory.
+    fn save_document(&self, doc: &PublishedDocument) -> Result<()> {
+        let path = self.tmp_dir.join(&doc.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        info!("Saving {} document at path {}", doc.kind.name(), doc.path);
+        self.save_json(&path, &doc.data)?;
         Ok(())
     }
 
-    /// Save list of all feeds.
-    fn save_json_feedlist(
+    /// Serialize data as json file at given path.
+    fn save_json<T: serde::Serialize + ?Sized>(
         &self,
-        feedlist: &FeedList,
-        name: String,
+        path: &Path,
+        data: &T,
     ) -> Result<()> {
-        if self.debug {
-            self.save_feed_data(
-                &name,
-                serde_json::to_string_pretty(&feedlist)?.as_bytes(),
+        let raw = match self.debug {
+            true => serde_json::to_string_pretty(data)?,
+            false => serde_json::to_string(data)?,
+        };
+        let mut file = File::create(path)?;
+        file.write_all(raw.as_bytes())?;
+        Ok(())
+    }
+
+    /// Create content hashed copies of template assets, rewriting
+    /// references in the rendered index and saving mapping of the
+    /// original paths to hashed ones.
+    fn fingerprint_assets(&self) -> Result<()> {
+        info!("Fingerprinting assets");
+        let mut assets = AssetMap::new();
+        fingerprint_dir(self.build_dir, ASSETS_DIRNAME, &mut assets)?;
+        let index_path =
+            self.build_dir.join(format!("{}.html", INDEX_FILENAME));
+        let html = fs::read_to_string(&index_path)?;
+        fs::write(&index_path, rewrite_references(&html, &assets))?;
+        self.save_json(&self.build_dir.join(ASSET_MANIFEST_FILENAME), &assets)?;
+        Ok(())
+    }
+
+    /// Save site metadata in the feeds directory.
+    fn save_site_meta(&self) -> Result<()> {
+        let meta = SiteMeta::init(
+            self.context.options(),
+            self.context.alternate_links(),
+            self.context.skipped_requests(),
+            self.context.build_time(),
+        );
+        self.save_document(&site_meta_document(&meta, FEEDS_DIRNAME)?)?;
+        Ok(())
+    }
+
+    /// Save sitemap listing html pages of the build directory
+    /// and robots exclusion file, sitemap is removed for
+    /// private sites. Both files require absolute urls so they
+    /// are skipped unless `site_url` is set (private sites still
+    /// get `robots.txt` disallowing all the paths).
+    fn save_site_files(&self) -> Result<()> {
+        let opts = self.context.options();
+        let sitemap_path = self.build_dir.join(SITEMAP_FILENAME);
+        if opts.private_site && sitemap_path.is_file() {
+            _ = fs::remove_file(&sitemap_path);
+        }
+        if !opts.has_site_url()
+            && !opts.private_site
+            && (opts.sitemap || opts.robots_txt)
+        {
+            warn!("site_url is not set, skipping sitemap and robots.txt");
+        }
+        if opts.sitemap && !opts.private_site && opts.has_site_url() {
+            let mut pages = Vec::new();
+            for entry in fs::read_dir(self.build_dir)? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if name.ends_with(".html") {
+                    pages.push(name);
+                }
+            }
+            pages.sort();
+            info!("Saving sitemap at {}", sitemap_path.display());
+            fs::write(
+                sitemap_path,
+                generate_sitemap(opts, &pages, self.context.build_time()),
             )?;
-        } else {
-            self.save_feed_data(
-                &name,
-                serde_json::to_string(&feedlist)?.as_bytes(),
+        }
+        if (opts.robots_txt && opts.has_site_url()) || opts.private_site {
+            if !opts.site_path.trim_end_matches('/').is_empty() {
+                warn!(
+                    "robots.txt is only honoured at the root of the host, \
+                    it will be ignored for site hosted at {}",
+                    opts.site_path
+                );
+            }
+            fs::write(
+                self.build_dir.join(ROBOTS_FILENAME),
+                generate_robots_txt(opts),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Save JSON Schema files describing all the API documents.
+    fn save_api_schemas(&self) -> Result<()> {
+        for doc in ApiDocument::ALL {
+            self.save_json(
+                &self.tmp_dir.join(doc.schema_path()),
+                &doc.schema(),
             )?;
         }
         Ok(())
@@ -304,26 +553,9 @@ where
             info!("Skipping saving feed: {:?}", feed);
             return Ok(());
         }
-        let mut truncated = feed.clone();
-        truncated.truncate_items();
-        if self.debug {
-            self.save_feed_data(
-                truncated.id(),
-                serde_json::to_string_pretty(&truncated)?.as_bytes(),
-            )?;
-            self.save_feed_data(
-                &format!("{}_archive", feed.id()),
-                serde_json::to_string_pretty(&feed)?.as_bytes(),
-            )?;
-        } else {
-            self.save_feed_data(
-                truncated.id(),
-                serde_json::to_string(&truncated)?.as_bytes(),
-            )?;
-            self.save_feed_data(
-                &format!("{}_archive", feed.id()),
-                serde_json::to_string(&feed)?.as_bytes(),
-            )?;
+        for doc in feed_documents(feed, self.context.options(), FEEDS_DIRNAME)?
+        {
+            self.save_document(&doc)?;
         }
         Ok(())
     }
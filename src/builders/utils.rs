@@ -1,15 +1,152 @@
 use log::info;
 use std::cmp::Reverse;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use atom_syndication::{
+    Feed as AtomFeed, FixedDateTime, Generator, Link as AtomLink,
+    Text as AtomText,
+};
+use chrono::DateTime;
 use opml::{Body, Head, Outline, OPML};
 use rss::{Channel, ChannelBuilder};
 use url::Url;
 
+use crate::dedup::collapse_duplicates;
 use crate::feed::Feed;
+use crate::feed_item::FeedItem;
 use crate::opts::Options;
+use crate::podcast::{
+    is_podcast_channel, podcast_item_extension, set_podcast_channel_extensions,
+};
 use crate::utils::now;
 
+/// Directory name used for storing self referential channel documents.
+pub const CHANNELS_DIRNAME: &str = "channels";
+
+/// Format of the channel documents generated for the feeds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelFormat {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+impl ChannelFormat {
+    /// Retrieve channel format from its name (`rss`, `atom`
+    /// or `json`), None is returned for unknown names.
+    pub fn from_name(name: &str) -> Option<ChannelFormat> {
+        match name.to_lowercase().as_str() {
+            "rss" => Some(ChannelFormat::Rss),
+            "atom" => Some(ChannelFormat::Atom),
+            "json" => Some(ChannelFormat::JsonFeed),
+            _ => None,
+        }
+    }
+
+    /// Path to the channel containing articles of all the feeds,
+    /// relative to the build directory.
+    pub fn site_channel_path(&self) -> String {
+        match self {
+            ChannelFormat::Rss => String::from("rss.xml"),
+            ChannelFormat::Atom => String::from("atom.xml"),
+            ChannelFormat::JsonFeed => String::from("feed.json"),
+        }
+    }
+
+    /// Path to the channel of single feed, relative
+    /// to the build directory.
+    pub fn feed_channel_path(&self, f: &Feed) -> String {
+        match self {
+            ChannelFormat::Rss => {
+                format!("{}/{}.xml", CHANNELS_DIRNAME, f.id())
+            }
+            ChannelFormat::Atom => {
+                format!("{}/{}.atom.xml", CHANNELS_DIRNAME, f.id())
+            }
+            ChannelFormat::JsonFeed => {
+                format!("{}/{}.json", CHANNELS_DIRNAME, f.id())
+            }
+        }
+    }
+
+    /// Whether or not channel in this format is published for the
+    /// feed, RSS channels are always available.
+    pub fn is_published(&self, opts: &Options, f: &Feed) -> bool {
+        match self {
+            ChannelFormat::Rss => true,
+            ChannelFormat::Atom => opts.atom_feeds,
+            ChannelFormat::JsonFeed => opts.json_feeds && !f.is_empty(),
+        }
+    }
+
+    /// Mime type of the channel documents.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ChannelFormat::Rss => "application/rss+xml",
+            ChannelFormat::Atom => "application/atom+xml",
+            ChannelFormat::JsonFeed => "application/feed+json",
+        }
+    }
+}
+
+/// Retrieve deduplicated articles of all the feeds to be
+/// included in the channel, sorted from newest to oldest.
+pub fn get_channel_items(feeds: &Vec<Feed>) -> Vec<FeedItem> {
+    let mut items = Vec::new();
+    let mut article_guids: Vec<i64> = Vec::new();
+    for feed in feeds {
+        let mut fc = feed.clone();
+        fc.truncate_items();
+
+        for feed_item in fc.items {
+            if article_guids.iter().any(|i| i == &feed_item.guid()) {
+                continue;
+            };
+            article_guids.push(feed_item.guid().clone());
+            items.push(feed_item)
+        }
+    }
+    let mut items = collapse_duplicates(items);
+    items.sort_by_key(|w| Reverse(w.date()));
+    return items;
+}
+
+/// Retrieve feeds aggregating articles for each of the tags,
+/// either defined for the feed or assigned to the article.
+/// Articles of hidden feeds are omitted.
+pub fn get_tag_feeds(feeds: &[Feed]) -> Vec<Feed> {
+    let url_feeds: Vec<&Feed> = feeds
+        .iter()
+        .filter(|f| !f.is_query_feed() && !f.is_hidden())
+        .collect();
+    let mut tags = BTreeSet::new();
+    for f in &url_feeds {
+        tags.extend(f.tags.iter().cloned());
+        for item in &f.items {
+            tags.extend(item.tags().iter().cloned());
+        }
+    }
+    let mut tag_feeds = Vec::new();
+    let mut ids = Vec::new();
+    for tag in tags {
+        let mut tag_feed = Feed::init_tag_feed(&tag, &ids);
+        for f in &url_feeds {
+            for item in &f.items {
+                if f.tags.contains(&tag) || item.tags().contains(&tag) {
+                    tag_feed.add_item(item.clone());
+                }
+            }
+        }
+        if tag_feed.is_empty() {
+            continue;
+        }
+        tag_feed.sort_items();
+        ids.push(tag_feed.id().clone());
+        tag_feeds.push(tag_feed);
+    }
+    return tag_feeds;
+}
+
 /// Generate RSS channel for the liveboat page.
 pub fn generate_rss_channel(
     opts: &Options,
@@ -30,29 +167,81 @@ pub fn generate_rss_channel(
             .link(opts.site_url.clone())
             .build()
     }
-    let mut items = Vec::new();
-    let mut article_guids: Vec<i64> = Vec::new();
-    for feed in feeds {
-        let mut fc = feed.clone();
-        fc.truncate_items();
-
-        for feed_item in fc.items {
-            if article_guids.iter().any(|i| i == &feed_item.guid()) {
-                continue;
-            };
-            article_guids.push(feed_item.guid().clone());
-            items.push(feed_item)
-        }
+    channel.set_last_build_date(now().to_rfc2822());
+    if opts.rss_channel_ttl > 0 {
+        channel.set_ttl(opts.rss_channel_ttl.to_string());
+    }
+    let items = get_channel_items(feeds);
+    let podcast = is_podcast_channel(opts, &items);
+    if podcast {
+        set_podcast_channel_extensions(&mut channel, opts);
     }
-    items.sort_by_key(|w| Reverse(w.date()));
     for item in items {
-        channel
-            .items
-            .push(item.to_rss_item(opts.include_article_content_in_rss_feeds))
+        let itunes_ext = match podcast {
+            true => podcast_item_extension(opts, &item),
+            false => None,
+        };
+        let mut rss_item =
+            item.to_rss_item(opts.include_article_content_in_rss_feeds);
+        rss_item.set_itunes_ext(itunes_ext);
+        channel.items.push(rss_item)
     }
     return channel.to_string();
 }
 
+/// Generate Atom feed for the liveboat page, `path` denotes
+/// location of the document relative to the site url.
+pub fn generate_atom_feed(
+    opts: &Options,
+    feeds: &Vec<Feed>,
+    general_channel: bool,
+    path: &str,
+) -> String {
+    info!("Generating atom feed");
+    let mut feed = AtomFeed::default();
+    let self_url = format!("{}/{}", opts.site_url.trim_end_matches('/'), path);
+    if general_channel {
+        feed.set_title(AtomText::plain(opts.title.clone()));
+        feed.set_subtitle(Some(AtomText::plain("Liveboat Atom Feed")));
+    } else {
+        feed.set_title(AtomText::plain(feeds[0].display_title().clone()));
+    }
+    feed.set_id(self_url.clone());
+    feed.set_links(vec![
+        AtomLink {
+            href: self_url,
+            rel: "self".to_string(),
+            mime_type: Some(ChannelFormat::Atom.mime_type().to_string()),
+            ..Default::default()
+        },
+        AtomLink {
+            href: opts.site_url.clone(),
+            rel: "alternate".to_string(),
+            ..Default::default()
+        },
+    ]);
+    feed.set_generator(Some(Generator {
+        value: String::from("Liveboat"),
+        uri: Some(String::from("https://github.com/exaroth/liveboat")),
+        version: None,
+    }));
+    let items = get_channel_items(feeds);
+    let updated: FixedDateTime = match items.first() {
+        Some(i) => DateTime::from_timestamp(i.date(), 0)
+            .unwrap_or_default()
+            .into(),
+        None => now().into(),
+    };
+    feed.set_updated(updated);
+    feed.set_entries(
+        items
+            .into_iter()
+            .map(|i| i.to_atom_entry(opts.include_article_content_in_rss_feeds))
+            .collect::<Vec<_>>(),
+    );
+    return feed.to_string();
+}
+
 /// Generate OPML file.
 pub fn generate_opml(
     opts: &Options,
@@ -91,31 +280,38 @@ pub fn generate_opml(
         o.title = Some(tag.clone());
         o.text = tag.clone();
         for f in tagged_feeds.get(tag).unwrap() {
-            let mut feed_outline = generate_feed_outline(f, site_url);
+            let mut feed_outline = generate_feed_outline(opts, f, site_url);
             feed_outline.category = Some(tag.clone());
             o.outlines.push(feed_outline)
         }
         body.outlines.push(o);
     }
     for f in bare_feeds {
-        body.outlines.push(generate_feed_outline(f, site_url))
+        body.outlines.push(generate_feed_outline(opts, f, site_url))
     }
     op.body = body.clone();
     return op.to_string().unwrap();
 }
 
-/// Generate outline instance from feed.
-fn generate_feed_outline(f: &Feed, site_url: &Url) -> Outline {
+/// Generate outline instance from feed, query feeds (and url feeds
+/// if `opml_liveboat_channels` is set) point to channels generated
+/// by liveboat in format defined in options, falling back to RSS
+/// if channel in that format is not published for the feed.
+fn generate_feed_outline(opts: &Options, f: &Feed, site_url: &Url) -> Outline {
     let mut feed_outline = Outline::default();
     feed_outline.title = Some(f.display_title().clone());
     feed_outline.text = f.display_title().clone();
     feed_outline.r#type = Some("rss".to_string());
+    let format = ChannelFormat::from_name(&opts.opml_channel_format)
+        .filter(|format| format.is_published(opts, f))
+        .unwrap_or(ChannelFormat::Rss);
+    let channel_url = site_url.join(&format.feed_channel_path(f)).unwrap();
     if f.is_query_feed() {
-        let channel_url = site_url
-            .join(format!("channels/{}.xml", f.id()).as_str())
-            .unwrap();
         feed_outline.xml_url = Some(channel_url.to_string());
         feed_outline.html_url = Some(site_url.to_string());
+    } else if opts.opml_liveboat_channels {
+        feed_outline.xml_url = Some(channel_url.to_string());
+        feed_outline.html_url = Some(f.feedlink().clone());
     } else {
         feed_outline.xml_url = Some(f.url().clone());
         feed_outline.html_url = Some(f.feedlink().clone());
@@ -127,7 +323,8 @@ fn generate_feed_outline(f: &Feed, site_url: &Url) -> Outline {
 mod tests {
 
     use super::*;
-    use crate::feed_item::FeedItem;
+    use std::cell::RefCell;
+    use std::sync::Arc;
 
     #[test]
     fn test_generating_rss_channel_from_feeds() {
@@ -202,7 +399,7 @@ mod tests {
 
         let result =
             generate_rss_channel(&Options::default(), &Vec::from([f1, f2, f3]), true);
-        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><item><title>item3</title><link>http://test3.com</link><author>exaroth</author><pubDate>Tue, 3 Dec 2024 04:26:40 +0000</pubDate></item><item><title>item2</title><link>http://test2.com</link><author>exaroth</author><pubDate>Mon, 2 Dec 2024 00:40:00 +0000</pubDate></item><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item><item><title>item4</title><link>http://test4.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item><item><title>item5</title><link>http://test5.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item></channel></rss>")
+        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><lastBuildDate>Thu, 12 Dec 2024 03:42:54 +0000</lastBuildDate><ttl>60</ttl><item><title>item3</title><link>http://test3.com</link><author>exaroth</author><guid>http://test3.com</guid><pubDate>Tue, 3 Dec 2024 04:26:40 +0000</pubDate><content:encoded><![CDATA[Test content 3]]></content:encoded></item><item><title>item2</title><link>http://test2.com</link><author>exaroth</author><guid>http://test2.com</guid><pubDate>Mon, 2 Dec 2024 00:40:00 +0000</pubDate><content:encoded><![CDATA[Test content 2]]></content:encoded></item><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><guid>http://test1.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 1]]></content:encoded></item><item><title>item4</title><link>http://test4.com</link><author>exaroth</author><guid>http://test4.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 4]]></content:encoded></item><item><title>item5</title><link>http://test5.com</link><author>exaroth</author><guid>http://test5.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 5]]></content:encoded></item></channel></rss>")
     }
 
     #[test]
@@ -227,7 +424,7 @@ mod tests {
         f1.items.push(item);
         let result =
             generate_rss_channel(&Options::default(), &Vec::from([f1]), true);
-        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><enclosure url=\"http://www.example.com/test.mp3\" length=\"\" type=\"audio/mp3\"/><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item></channel></rss>")
+        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><lastBuildDate>Thu, 12 Dec 2024 03:42:54 +0000</lastBuildDate><ttl>60</ttl><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><enclosure url=\"http://www.example.com/test.mp3\" length=\"\" type=\"audio/mp3\"/><guid>http://test1.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 1]]></content:encoded></item></channel></rss>")
     }
 
     #[test]
@@ -250,7 +447,7 @@ mod tests {
         f1.items.push(item);
         let result =
             generate_rss_channel(&Options::default(), &Vec::from([f1]), false);
-        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\"><channel><title>Test feed 1</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description></description><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item></channel></rss>")
+        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"><channel><title>Test feed 1</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description></description><lastBuildDate>Thu, 12 Dec 2024 03:42:54 +0000</lastBuildDate><ttl>60</ttl><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><guid>http://test1.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 1]]></content:encoded></item></channel></rss>")
     }
 
     #[test]
@@ -278,7 +475,7 @@ mod tests {
             &Vec::from([f, qf]),
             true,
         );
-        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item></channel></rss>")
+        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><lastBuildDate>Thu, 12 Dec 2024 03:42:54 +0000</lastBuildDate><ttl>60</ttl><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><guid>http://test1.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 1]]></content:encoded></item></channel></rss>")
     }
     #[test]
     fn test_generting_rss_with_hidden_feeds() {
@@ -301,7 +498,36 @@ mod tests {
         f.items.push(i1);
         let result =
             generate_rss_channel(&Options::default(), &Vec::from([f]), true);
-        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate></item></channel></rss>")
+        assert_eq!(result,  "<?xml version=\"1.0\" encoding=\"utf-8\"?><rss version=\"2.0\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"><channel><title>Liveboat feed page</title><link>http://site-url-not-set.io/you-can-set-it-in-liveboat-config</link><description>Liveboat RSS Feed</description><lastBuildDate>Thu, 12 Dec 2024 03:42:54 +0000</lastBuildDate><ttl>60</ttl><item><title>item1</title><link>http://test1.com</link><author>exaroth</author><guid>http://test1.com</guid><pubDate>Sat, 30 Nov 2024 20:53:20 +0000</pubDate><content:encoded><![CDATA[Test content 1]]></content:encoded></item></channel></rss>")
+    }
+
+    #[test]
+    fn test_generating_atom_feed() {
+        let mut f1 = Feed::init(
+            "www.example.com/rss".to_string(),
+            "Test feed 1".to_string(),
+            "www.example.com".to_string(),
+        );
+        f1.tags.push("dev".to_string());
+        let f1 = Arc::new(RefCell::new(f1));
+        let mut item = FeedItem::new(
+            "item1",
+            "http://test1.com",
+            "",
+            "exaroth",
+            1733000000,
+            false,
+            "<p>Test content 1</p>",
+            1,
+        );
+        item.set_comments_url("http://comments.com/1".to_string());
+        item.add_tag("rust".to_string());
+        item.set_ptr(Arc::clone(&f1));
+        f1.borrow_mut().items.push(item);
+        let feeds = Vec::from([f1.borrow().clone()]);
+        let result =
+            generate_atom_feed(&Options::default(), &feeds, false, "atom.xml");
+        assert_eq!(result, "<?xml version=\"1.0\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Test feed 1</title><id>http://site-url-not-set.io/you-can-set-it-in-liveboat-config/atom.xml</id><updated>2024-11-30T20:53:20+00:00</updated><generator uri=\"https://github.com/exaroth/liveboat\">Liveboat</generator><link href=\"http://site-url-not-set.io/you-can-set-it-in-liveboat-config/atom.xml\" rel=\"self\" type=\"application/atom+xml\"/><link href=\"http://site-url-not-set.io/you-can-set-it-in-liveboat-config\" rel=\"alternate\"/><entry><title>item1</title><id>http://test1.com</id><updated>2024-11-30T20:53:20+00:00</updated><author><name>exaroth</name></author><category term=\"rust\"/><category term=\"dev\"/><link href=\"http://test1.com\" rel=\"alternate\"/><link href=\"http://comments.com/1\" rel=\"related\"/><published>2024-11-30T20:53:20+00:00</published><content type=\"html\">&lt;p&gt;Test content 1&lt;/p&gt;</content></entry></feed>")
     }
 
     #[test]
@@ -326,4 +552,115 @@ mod tests {
         );
         assert_eq!("<opml version=\"2.0\"><head><title>Liveboat feed page</title><dateCreated>Thu, 12 Dec 2024 03:42:54 +0000</dateCreated><dateModified>Thu, 12 Dec 2024 03:42:54 +0000</dateModified></head><body><outline text=\"test\" title=\"test\"><outline text=\"Test feed 2\" type=\"rss\" category=\"test\" xmlUrl=\"www.test2.com/rss\" htmlUrl=\"www.example2.com\" title=\"Test feed 2\"/></outline><outline text=\"Test feed 1\" type=\"rss\" xmlUrl=\"www.test1.com/rss\" htmlUrl=\"www.example.com\" title=\"Test feed 1\"/><outline text=\"Query feed\" type=\"rss\" xmlUrl=\"http://www.example.com/channels/5aSKHsoqmmoCnw.xml\" htmlUrl=\"http://www.example.com/\" title=\"Query feed\"/></body></opml>", result)
     }
+
+    #[test]
+    fn test_generating_tag_feeds() {
+        let mut f1 = Feed::init(
+            "www.test1.com/rss".to_string(),
+            "Test feed 1".to_string(),
+            "www.example.com".to_string(),
+        );
+        f1.tags.push("dev".to_string());
+        let mut f2 = Feed::init(
+            "www.test2.com/rss".to_string(),
+            "Test feed 2".to_string(),
+            "www.example2.com".to_string(),
+        );
+        let mut f3 = f2.clone();
+        f3.update_with_url_data(vec!["hidden".to_string()], true, None, 2);
+        for (idx, title) in ["item1", "item2", "item3"].iter().enumerate() {
+            let mut item = FeedItem::new(
+                title,
+                "http://test.com",
+                "",
+                "",
+                1733000000 + idx as i64,
+                false,
+                "",
+                idx as i64,
+            );
+            match idx {
+                0 => f1.add_item(item),
+                1 => {
+                    item.add_tag("Rust Lang".to_string());
+                    item.add_tag("rust-lang".to_string());
+                    f2.add_item(item);
+                }
+                _ => f3.add_item(item),
+            }
+        }
+        let tag_feeds = get_tag_feeds(&vec![f1, f2, f3]);
+        assert_eq!(3, tag_feeds.len());
+        assert_eq!("Rust Lang", tag_feeds[0].display_title());
+        assert_eq!("tag-rust-lang", tag_feeds[0].id());
+        assert_eq!(
+            "channels/tag-rust-lang.xml",
+            ChannelFormat::Rss.feed_channel_path(&tag_feeds[0])
+        );
+        assert_eq!("item2", tag_feeds[0].items[0].title());
+        assert_eq!("tag-dev", tag_feeds[1].id());
+        assert_eq!(1, tag_feeds[1].items.len());
+        assert_eq!("rust-lang", tag_feeds[2].display_title());
+        assert!(tag_feeds[2].id().starts_with("tag-rust-lang-"));
+        assert_eq!("tag-rust-lang-".len() + 6, tag_feeds[2].id().len());
+        assert_eq!("tag-c-", Feed::init_tag_feed(&"C#".to_string(), &[]).id());
+        let taken = vec!["tag-c-".to_string()];
+        assert_ne!(
+            Feed::init_tag_feed(&"C#".to_string(), &taken).id(),
+            Feed::init_tag_feed(&"C+".to_string(), &taken).id()
+        );
+    }
+
+    #[test]
+    fn test_retrieving_channel_format_from_name() {
+        assert_eq!(Some(ChannelFormat::Rss), ChannelFormat::from_name("rss"));
+        assert_eq!(
+            Some(ChannelFormat::JsonFeed),
+            ChannelFormat::from_name("JSON")
+        );
+        assert_eq!(None, ChannelFormat::from_name("atm"));
+    }
+
+    #[test]
+    fn test_generating_opml_with_liveboat_channels() {
+        let f1 = Feed::init(
+            "www.test1.com/rss".to_string(),
+            "Test feed 1".to_string(),
+            "www.example.com".to_string(),
+        );
+        let expected = format!(
+            "xmlUrl=\"http://www.example.com/channels/{}.atom.xml\" htmlUrl=\"www.example.com\"",
+            f1.id()
+        );
+        let mut opts = Options::default();
+        opts.opml_liveboat_channels = true;
+        opts.opml_channel_format = "atom".to_string();
+        let result = generate_opml(
+            &opts,
+            &Vec::from([f1.clone()]),
+            &Url::parse("http://www.example.com").unwrap(),
+        );
+        assert!(result.contains(&expected));
+
+        // Channels not published for the feed fall back to RSS.
+        let expected = format!(
+            "xmlUrl=\"http://www.example.com/channels/{}.xml\"",
+            f1.id()
+        );
+        opts.atom_feeds = false;
+        let result = generate_opml(
+            &opts,
+            &Vec::from([f1.clone()]),
+            &Url::parse("http://www.example.com").unwrap(),
+        );
+        assert!(result.contains(&expected));
+        opts.opml_channel_format = "json".to_string();
+        opts.json_feeds = true;
+        let result = generate_opml(
+            &opts,
+            &Vec::from([f1]),
+            &Url::parse("http://www.example.com").unwrap(),
+        );
+        assert!(result.contains(&expected));
+    }
 }
@@ -0,0 +1,167 @@
+/// This module contains cache of scraped article content, persisted
+/// between builds so that offline builds can reuse previously
+/// retrieved data.
+use log::info;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{read_to_string, File};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::now;
+
+/// Single cached scrape result.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CachedContent {
+    /// Content of the article including html tags.
+    pub content: String,
+    /// Raw content text of the article.
+    pub text: String,
+    /// Timestamp of the scrape.
+    pub timestamp: i64,
+    /// Lead image defined in page metadata.
+    #[serde(default)]
+    pub lead_image: Option<String>,
+}
+
+/// Url keyed cache of scraped article content.
+#[derive(Debug, Default)]
+pub struct ContentCache {
+    entries: RefCell<HashMap<String, CachedContent>>,
+}
+
+impl ContentCache {
+    /// Load cache from JSON file, missing or invalid files
+    /// result in empty cache.
+    pub fn load(path: &Path) -> ContentCache {
+        if !path.is_file() {
+            return ContentCache::default();
+        }
+        let entries = read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(serde_json::from_str(&raw)?));
+        match entries {
+            Ok(e) => ContentCache {
+                entries: RefCell::new(e),
+            },
+            Err(e) => {
+                info!("Could not load content cache: {}", e);
+                ContentCache::default()
+            }
+        }
+    }
+
+    /// Save cache as JSON file, file of the cache without
+    /// any entries is removed instead.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if self.is_empty() {
+            if path.is_file() {
+                std::fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut f = File::create(path)?;
+        f.write_all(serde_json::to_string(&self.entries)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Retrieve cached content for given url.
+    pub fn get(&self, url: &str) -> Option<CachedContent> {
+        return self.entries.borrow().get(url).cloned();
+    }
+
+    /// Store scraped content for given url.
+    pub fn insert(
+        &self,
+        url: &str,
+        content: &str,
+        text: &str,
+        lead_image: &Option<String>,
+    ) {
+        self.entries.borrow_mut().insert(
+            url.to_string(),
+            CachedContent {
+                content: content.to_string(),
+                text: text.to_string(),
+                timestamp: now().timestamp(),
+                lead_image: lead_image.clone(),
+            },
+        );
+    }
+
+    /// Remove entries older than given number of days.
+    pub fn prune(&self, days: u64) {
+        let cutoff = now().timestamp() - (days as i64) * 24 * 60 * 60;
+        self.entries
+            .borrow_mut()
+            .retain(|_, c| c.timestamp >= cutoff);
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        return self.entries.borrow().len();
+    }
+
+    /// Whether or not the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        return self.entries.borrow().is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_storing_and_pruning_cached_content() {
+        let cache = ContentCache::default();
+        cache.insert("http://test.com/1", "<p>Content</p>", "Content", &None);
+        let entry = cache.get("http://test.com/1").unwrap();
+        assert_eq!("<p>Content</p>", entry.content);
+        assert_eq!("Content", entry.text);
+        assert!(cache.get("http://test.com/2").is_none());
+
+        cache.entries.borrow_mut().insert(
+            "http://test.com/old".to_string(),
+            CachedContent {
+                content: String::new(),
+                text: String::new(),
+                timestamp: 1433974974,
+                lead_image: None,
+            },
+        );
+        assert_eq!(2, cache.len());
+        cache.prune(20);
+        assert_eq!(1, cache.len());
+        assert!(cache.get("http://test.com/1").is_some());
+    }
+
+    #[test]
+    fn test_loading_missing_cache_file() {
+        let cache = ContentCache::load(Path::new("/nonexistent/cache.json"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_saving_cache() {
+        let path = std::env::temp_dir().join(format!(
+            "liveboat_content_cache_{}.json",
+            std::process::id()
+        ));
+        let cache = ContentCache::default();
+        cache.insert("http://test.com/1", "<p>Content</p>", "Content", &None);
+        cache.save(&path).unwrap();
+        assert_eq!(1, ContentCache::load(&path).len());
+
+        // Cache file is removed once all the entries expire.
+        ContentCache::default().save(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -3,9 +3,23 @@ use log::info;
 use anyhow::Result;
 use readability_liveboat::extractor;
 use regex::Regex;
+use serde::Serialize;
 use url::Url;
 
+use crate::cache::ContentCache;
+use crate::errors::HttpError;
+use crate::http::HttpClient;
 use crate::opts::Options;
+use crate::utils::get_html_attribute;
+
+/// List of Reddit domains serving RSS feeds which contain direct
+/// article links in the item content.
+const REDDIT_FEED_DOMAINS: &[&str] = &[
+    "www.reddit.com",
+    "old.reddit.com",
+    "new.reddit.com",
+    "reddit.com",
+];
 
 /// List of domains associated with Reddit which will
 /// be marked as self referential (and excluded from scraping).
@@ -21,6 +35,61 @@ const REDDIT_SELF_REFERENTIAL_DOMAINS: &[&str] = &[
 const SCRAPE_EXCLUDED_DOMAINS: &[&str] =
     &["github.com", "github.io", "bloomberg.com", "youtube.com"];
 
+/// Meta tag names containing lead image of the page,
+/// in order of preference.
+const LEAD_IMAGE_META_TAGS: &[&str] = &[
+    "og:image",
+    "og:image:url",
+    "og:image:secure_url",
+    "twitter:image",
+    "twitter:image:src",
+];
+
+/// List of domains which are never treated as article links when
+/// searching Slashdot item content (share buttons, feed proxies etc).
+const SLASHDOT_IGNORED_LINK_DOMAINS: &[&str] = &[
+    "slashdot.org",
+    "feedburner.com",
+    "feedproxy.google.com",
+    "twitter.com",
+    "facebook.com",
+    "linkedin.com",
+];
+
+/// Links retrieved from discussion aggregator content,
+/// article url will point to the same page as comments url
+/// for self posts.
+#[derive(Debug, PartialEq)]
+struct DiscussionLinks {
+    article_url: Url,
+    comments_url: String,
+}
+
+impl DiscussionLinks {
+    /// Whether or not article url points to external site
+    /// (as opposed to the discussion page itself).
+    fn is_external(&self) -> bool {
+        return self.article_url.as_str() != self.comments_url;
+    }
+}
+
+/// Maximum length of the text for which rejection markers are
+/// checked, longer texts are unlikely to be consent or paywall pages.
+const REJECTION_MARKER_MAX_TEXT_LENGTH: usize = 2000;
+
+/// Source of the article content used in the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentSource {
+    /// Content extracted from the scraped article page.
+    Scraped,
+    /// Content extracted from the feed item content.
+    Extracted,
+    /// Raw feed item content.
+    #[default]
+    Feed,
+}
+
 /// Representation of content processing result.
 #[derive(Debug)]
 pub struct ContentProcessingResult {
@@ -28,6 +97,34 @@ pub struct ContentProcessingResult {
     pub text: String,
     pub url: String,
     pub comments_url: Option<String>,
+    pub discussion: Option<DiscussionMetadata>,
+    /// Lead image as defined in meta tags of the scraped page.
+    pub lead_image: Option<String>,
+    /// Source of the selected content.
+    pub content_source: ContentSource,
+}
+
+/// Discussion metadata as exposed by aggregator feeds
+/// (hnrss.org, Reddit, Lemmy).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscussionMetadata {
+    /// Number of points/upvotes the submission received.
+    pub score: Option<i64>,
+    /// Number of comments in the discussion.
+    pub comment_count: Option<i64>,
+    /// Name of the user who submitted the article.
+    pub submitter: Option<String>,
+}
+
+/// Metadata derived from the article text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextMetadata {
+    /// Number of words in the article.
+    pub word_count: usize,
+    /// Estimated reading time in minutes.
+    pub reading_time: usize,
+    /// ISO 639-3 code of the detected article language.
+    pub language: Option<String>,
 }
 
 impl ContentProcessingResult {
@@ -45,25 +142,128 @@ impl ContentProcessingResult {
             text: String::new(),
             url: url,
             comments_url: None,
+            discussion: None,
+            lead_image: None,
+            content_source: ContentSource::Feed,
         };
     }
 }
 
+/// Calculate word count, reading time and language for the article
+/// text, language is only set if detection is reliable.
+pub fn get_text_metadata(text: &str, reading_speed: u64) -> TextMetadata {
+    let word_count = text.split_whitespace().count();
+    let reading_time = match reading_speed {
+        0 => 0,
+        s => word_count.div_ceil(s as usize),
+    };
+    let language = whatlang::detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code().to_string());
+    return TextMetadata {
+        word_count,
+        reading_time,
+        language,
+    };
+}
+
+/// Generate plain text excerpt of the article, cutting the text on
+/// sentence boundaries. Falls back to cutting on word boundary
+/// if first sentence exceeds the maximum length.
+pub fn get_excerpt(text: &str, max_length: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if text.chars().count() <= max_length {
+        return text;
+    }
+    let sentence_re = Regex::new(r#"[^.!?]+[.!?]+["')\]]*(\s|$)"#).unwrap();
+    let mut excerpt = "";
+    for sentence in sentence_re.find_iter(&text) {
+        let candidate = text[..sentence.end()].trim_end();
+        if candidate.chars().count() > max_length {
+            break;
+        }
+        excerpt = candidate;
+    }
+    if !excerpt.is_empty() {
+        return excerpt.to_string();
+    }
+    let mut excerpt = String::new();
+    for word in text.split(' ') {
+        if excerpt.chars().count() + word.chars().count() + 1 > max_length {
+            break;
+        }
+        if !excerpt.is_empty() {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+    return format!("{}…", excerpt);
+}
+
+/// Retrieve lead image of the page defined by Open Graph
+/// or Twitter meta tags.
+pub fn get_meta_image(html: &str, base_url: &Url) -> Option<String> {
+    let meta_re = Regex::new(r#"(?i)<meta\b([^>]*)>"#).unwrap();
+    let mut images: Vec<(usize, String)> = Vec::new();
+    for meta in meta_re.captures_iter(html) {
+        let name = get_html_attribute(&meta[1], "property")
+            .or(get_html_attribute(&meta[1], "name"))
+            .unwrap_or_default()
+            .to_lowercase();
+        let Some(pos) = LEAD_IMAGE_META_TAGS.iter().position(|t| *t == name)
+        else {
+            continue;
+        };
+        if let Some(content) = get_html_attribute(&meta[1], "content") {
+            images.push((pos, content));
+        }
+    }
+    images.sort_by_key(|i| i.0);
+    return images
+        .into_iter()
+        .find_map(|(_, img)| resolve_image_url(&img, base_url));
+}
+
+/// Retrieve first image included in the article content.
+pub fn get_first_image(content: &str, base_url: &Url) -> Option<String> {
+    let img_re = Regex::new(r#"(?i)<img\b([^>]*)>"#).unwrap();
+    return img_re.captures_iter(content).find_map(|img| {
+        let src = get_html_attribute(&img[1], "src")?;
+        return resolve_image_url(&src, base_url);
+    });
+}
+
+/// Resolve image url against base url, inline images
+/// and non http urls are ignored.
+fn resolve_image_url(src: &str, base_url: &Url) -> Option<String> {
+    let url = base_url.join(src.trim()).ok()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    return Some(url.to_string());
+}
+
 /// Process article content, filtering all extraneous data
-/// and retrieving comments urls when necessary
+/// and retrieving comments urls when necessary. Scraped content
+/// is stored in the cache and reused when network access is disabled.
 pub fn process_article_content(
     url_string: &String,
     feedlink: &String,
     feed_url: &String,
     original_content: &mut String,
     options: &Options,
+    client: &HttpClient,
+    cache: &ContentCache,
 ) -> Result<ContentProcessingResult> {
     // Wrap original content in article tag so that content links without any html
     // tags can still be processed by the extractor. (kw)
+    let raw_content = original_content.clone();
     let original_content = format!("<article>{}</article>", original_content);
     let mut scrape = false;
     let mut result = ContentProcessingResult::default(url_string.clone());
     let mut url = Url::parse(url_string)?;
+    result.discussion =
+        get_discussion_metadata(&url, feed_url, &original_content);
     if options.scrape_reddit_links {
         let r_res = get_reddit_direct_link(&url, &original_content);
         if r_res.is_some() {
@@ -81,47 +281,261 @@ pub fn process_article_content(
             scrape = h_parts.1
         }
     }
-    if SCRAPE_EXCLUDED_DOMAINS
-        .iter()
-        .any(|d| url.host().unwrap().to_string().contains(d))
+    if options.scrape_aggregator_links && !scrape {
+        if let Some(links) =
+            get_aggregator_links(&url, feed_url, &original_content)
+        {
+            scrape = links.is_external();
+            if links.article_url != url {
+                result.url = links.article_url.to_string();
+                url = links.article_url;
+            }
+            result.comments_url = Some(links.comments_url);
+        }
+    }
+    if url
+        .host_str()
+        .is_some_and(|h| SCRAPE_EXCLUDED_DOMAINS.iter().any(|d| h.contains(d)))
     {
         info!("excluding domain from scraping {:?}", url_string);
         scrape = false;
     }
-    let extract_result: Result<
-        extractor::Product,
-        readability_liveboat::error::Error,
-    >;
+    let mut candidates: Vec<(ContentSource, extractor::Product)> = Vec::new();
     if scrape {
-        extract_result = extractor::scrape(&result.url.as_str());
-    } else {
-        extract_result =
-            extractor::extract(&mut original_content.as_bytes(), &url);
+        let scrape_result = match cache.get(url.as_str()) {
+            Some(cached) if client.is_offline() => {
+                result.lead_image = cached.lead_image;
+                Ok(extractor::Product {
+                    title: String::new(),
+                    content: cached.content,
+                    text: cached.text,
+                })
+            }
+            _ => scrape_article(client, &url).map(|(product, image)| {
+                result.lead_image = image;
+                product
+            }),
+        };
+        match scrape_result {
+            Ok(t) => {
+                if !client.is_offline() {
+                    cache.insert(
+                        url.as_str(),
+                        &t.content,
+                        &t.text,
+                        &result.lead_image,
+                    );
+                }
+                candidates.push((ContentSource::Scraped, t));
+            }
+            Err(e) => info!("Error scraping content for {}: {}", url, e),
+        }
+    }
+    if !candidates
+        .iter()
+        .any(|c| is_acceptable_content(&c.1.text, options))
+    {
+        match extractor::extract(&mut original_content.as_bytes(), &url) {
+            Ok(t) => candidates.push((ContentSource::Extracted, t)),
+            Err(e) => info!("Error extracting content for {}: {}", url, e),
+        }
     }
-    if extract_result.is_ok() {
-        let t = extract_result.unwrap();
-        result.content = t.content;
-        result.text = t.text;
+    if !candidates
+        .iter()
+        .any(|c| is_acceptable_content(&c.1.text, options))
+    {
+        candidates.push((
+            ContentSource::Feed,
+            extractor::Product {
+                title: String::new(),
+                text: strip_html_tags(&raw_content),
+                content: raw_content,
+            },
+        ));
     }
+    let (source, product) = select_content(candidates, options);
+    result.content_source = source;
+    result.content = product.content;
+    result.text = product.text;
 
     Ok(result)
 }
 
+/// Check if article text is long enough and does not look like
+/// consent or paywall page.
+fn is_acceptable_content(text: &str, options: &Options) -> bool {
+    let length = text.trim().chars().count();
+    if length == 0 || length < options.content_min_text_length as usize {
+        return false;
+    }
+    if length > REJECTION_MARKER_MAX_TEXT_LENGTH {
+        return true;
+    }
+    let text = text.to_lowercase();
+    return !options
+        .content_rejection_markers
+        .iter()
+        .any(|m| text.contains(&m.to_lowercase()));
+}
+
+/// Pick content candidate, candidates are ordered by preference and
+/// first acceptable one is used, if none are acceptable the
+/// one with the longest text is selected instead.
+fn select_content(
+    candidates: Vec<(ContentSource, extractor::Product)>,
+    options: &Options,
+) -> (ContentSource, extractor::Product) {
+    let pos = candidates
+        .iter()
+        .position(|c| is_acceptable_content(&c.1.text, options))
+        .or_else(|| {
+            candidates
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, c)| c.1.text.trim().chars().count())
+                .map(|(i, _)| i)
+        });
+    return match pos {
+        Some(p) => candidates.into_iter().nth(p).unwrap(),
+        None => (
+            ContentSource::Feed,
+            extractor::Product {
+                title: String::new(),
+                content: String::new(),
+                text: String::new(),
+            },
+        ),
+    };
+}
+
+/// Strip html tags from the content, retaining only text.
+pub fn strip_html_tags(content: &str) -> String {
+    let tag_re = Regex::new(r#"(?s)<[^>]*>"#).unwrap();
+    let text = tag_re.replace_all(content, " ");
+    return text.split_whitespace().collect::<Vec<&str>>().join(" ");
+}
+
+/// Fetch article page using shared client and extract its content
+/// along with the lead image defined in page metadata.
+fn scrape_article(
+    client: &HttpClient,
+    url: &Url,
+) -> Result<(extractor::Product, Option<String>)> {
+    let response = client.get(url.as_str())?;
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    if !content_type.contains("text/html") {
+        return Err(HttpError::InvalidContentType(content_type).into());
+    }
+    let body = response.text()?;
+    let image = get_meta_image(&body, url);
+    return Ok((extractor::extract(&mut body.as_bytes(), url)?, image));
+}
+
+/// Retrieve discussion metadata (score, comment count and submitter)
+/// from aggregator feed content, feeds with urls which can't be parsed
+/// (eg. newsboat `exec:` and `filter:` urls) are skipped.
+pub fn get_discussion_metadata(
+    url: &Url,
+    feed_url: &str,
+    content: &str,
+) -> Option<DiscussionMetadata> {
+    let f_url = Url::parse(feed_url).ok()?;
+    let meta = match f_url.host_str() {
+        Some("hnrss.org") => get_hnrss_org_metadata(content),
+        Some(_) if is_lemmy_feed(&f_url, content) => {
+            get_lemmy_metadata(content)
+        }
+        _ => match url.host_str() {
+            Some(h) if REDDIT_FEED_DOMAINS.contains(&h) => {
+                get_reddit_metadata(content)
+            }
+            _ => DiscussionMetadata::default(),
+        },
+    };
+    if meta == DiscussionMetadata::default() {
+        return None;
+    }
+    return Some(meta);
+}
+
+/// Retrieve points and comment count from hnrss.org content.
+fn get_hnrss_org_metadata(content: &str) -> DiscussionMetadata {
+    // Matching on:
+    // <p>Points: 123</p>
+    // <p># Comments: 45</p>
+    let points_re = Regex::new(r#"<p>Points: (?<points>\d+)</p>"#).unwrap();
+    let comments_re =
+        Regex::new(r#"<p># Comments: (?<comments>\d+)</p>"#).unwrap();
+    return DiscussionMetadata {
+        score: points_re
+            .captures(content)
+            .and_then(|c| c["points"].parse().ok()),
+        comment_count: comments_re
+            .captures(content)
+            .and_then(|c| c["comments"].parse().ok()),
+        submitter: None,
+    };
+}
+
+/// Retrieve submitter name from Reddit content, Reddit feeds
+/// do not expose score nor comment counts.
+fn get_reddit_metadata(content: &str) -> DiscussionMetadata {
+    // Matching on:
+    // submitted by <a href="https://www.reddit.com/user/<name>"> /u/<name> </a>
+    let re = Regex::new(
+        r#"submitted by\s*<a[^>]*href\s?=['"]*[^'"]*/user/(?<user>[^/'"]+)"#,
+    )
+    .unwrap();
+    return DiscussionMetadata {
+        submitter: re.captures(content).map(|c| c["user"].to_string()),
+        ..DiscussionMetadata::default()
+    };
+}
+
+/// Retrieve score, comment count and submitter from Lemmy content.
+fn get_lemmy_metadata(content: &str) -> DiscussionMetadata {
+    // Matching on:
+    // submitted by <a href="https://<instance>/u/<name>">name</a> to ...
+    // 12 points | <a href="https://<instance>/post/<id>">3 comments</a>
+    let submitter_re =
+        Regex::new(r#"submitted by\s*<a[^>]*>(?<user>[^<]+)</a>"#).unwrap();
+    let counts_re = Regex::new(
+        r#"(?<points>-?\d+) points? \| <a[^>]*>(?<comments>\d+) comments?</a>"#,
+    )
+    .unwrap();
+    let counts = counts_re.captures(content);
+    return DiscussionMetadata {
+        score: counts.as_ref().and_then(|c| c["points"].parse().ok()),
+        comment_count: counts.as_ref().and_then(|c| c["comments"].parse().ok()),
+        submitter: submitter_re
+            .captures(content)
+            .map(|c| c["user"].trim().to_string()),
+    };
+}
+
 /// Dispatch processing for HN related feeds (hnrss.org and
 /// native news.ycombinator.com native feed), retrieving
 /// comment urls and deciding whether to dispatch scrape
 /// processing for associated urls.
 fn get_hn_links(
     url: &Url,
-    feedlink: &String,
-    feed_url: &String,
-    content: &String,
+    feedlink: &str,
+    feed_url: &str,
+    content: &str,
 ) -> Result<Option<(String, bool)>> {
     // Don't process self referential links for now.
     if url.path() == "news.ycombinator.com" {
         return Ok(Some((url.to_string(), false)));
     }
-    let f_url = Url::parse(feed_url)?;
+    let Ok(f_url) = Url::parse(feed_url) else {
+        return Ok(None);
+    };
     let host_opt = f_url.host();
     if host_opt.is_none() {
         return Ok(None);
@@ -137,11 +551,9 @@ fn get_hn_links(
 }
 
 /// Fetch direct link from Reddits RSS content.
-fn get_reddit_direct_link(url: &Url, content: &String) -> Option<Url> {
-    let host = url.host();
-    if host.is_none()
-        || host.unwrap().to_string() != String::from("www.reddit.com")
-    {
+fn get_reddit_direct_link(url: &Url, content: &str) -> Option<Url> {
+    let host = url.host_str();
+    if host.is_none() || !REDDIT_FEED_DOMAINS.contains(&host.unwrap()) {
         return None;
     }
     let re =
@@ -157,8 +569,8 @@ fn get_reddit_direct_link(url: &Url, content: &String) -> Option<Url> {
         info!("Invalid number of matches in response");
         return None;
     }
-    let res = Url::parse(&caps[1]);
-    if res.is_err() {
+    let res = parse_http_link(&caps[1]);
+    if res.is_none() {
         info!("Cant parse url: {}", &caps[0]);
         return None;
     }
@@ -187,7 +599,7 @@ const SCRAPE_EXCLUDED_HN_SECTIONS: &[&str] = &["/ask"];
 /// be scraped based on the HN section.
 fn get_hnrss_org_links(
     feedlink: &Url,
-    content: &String,
+    content: &str,
 ) -> Option<(String, bool)> {
     // Matching on:
     // <p>Comments URL: <a href="?(<url>")>
@@ -216,7 +628,7 @@ fn get_hnrss_org_links(
 
 /// Retrieve comment link from native hn rss feed
 /// (news.ycombinator.com//rss)
-fn get_native_hn_links(content: &String) -> Option<(String, bool)> {
+fn get_native_hn_links(content: &str) -> Option<(String, bool)> {
     let re = Regex::new(r#"<a.*href\s?=['"]*(?<href>[^'"]*)[^>]*>"#).unwrap();
     let cap_result = re.captures(content);
     if cap_result.is_none() {
@@ -231,3 +643,489 @@ fn get_native_hn_links(content: &String) -> Option<(String, bool)> {
     // Note: for native hn feeds we will dispatch scrape for all links
     return Some((caps[1].to_string(), true));
 }
+
+/// Dispatch processing for discussion aggregator feeds other than
+/// HN and Reddit (lobste.rs, tildes.net, Lemmy instances and Slashdot),
+/// retrieving comments url and direct link to the article. Feeds with
+/// urls which can't be parsed are not treated as aggregators.
+fn get_aggregator_links(
+    url: &Url,
+    feed_url: &str,
+    content: &str,
+) -> Option<DiscussionLinks> {
+    let f_url = Url::parse(feed_url).ok()?;
+    let host = f_url.host_str()?;
+    if host == "lobste.rs" {
+        return get_lobsters_links(url, content);
+    }
+    if host == "tildes.net" {
+        return get_tildes_links(url, content);
+    }
+    if host.ends_with("slashdot.org") {
+        return get_slashdot_links(url, content);
+    }
+    if is_lemmy_feed(&f_url, content) {
+        return get_lemmy_links(url, content);
+    }
+    return None;
+}
+
+/// Parse link retrieved from the aggregator content, only
+/// absolute http(s) links are accepted.
+fn parse_http_link(href: &str) -> Option<Url> {
+    let url = Url::parse(href).ok()?;
+    if (url.scheme() != "http" && url.scheme() != "https")
+        || url.host_str().is_none()
+    {
+        return None;
+    }
+    return Some(url);
+}
+
+/// Check if feed is published by Lemmy instance, feed url has to
+/// match one of the Lemmy feed paths (eg. `/feeds/c/<community>.xml`)
+/// and the content has to link to a post on the same instance.
+fn is_lemmy_feed(f_url: &Url, content: &str) -> bool {
+    let path_re =
+        Regex::new(r#"^/feeds/(?:all|local|(?:c|u|front|inbox)/[^/]+)\.xml$"#)
+            .unwrap();
+    let Some(host) = f_url.host_str() else {
+        return false;
+    };
+    return path_re.is_match(f_url.path())
+        && content.contains(&format!("://{}/post/", host));
+}
+
+/// Retrieve comments url from lobste.rs feed item, item url
+/// points directly to the article (or to discussion page for
+/// text posts).
+fn get_lobsters_links(url: &Url, content: &str) -> Option<DiscussionLinks> {
+    // Matching on:
+    // <a href="https://lobste.rs/s/<id>/<slug>">Comments</a>
+    let re = Regex::new(
+        r#"<a[^>]*href\s?=['"]*(?<href>https?://lobste\.rs/s/[^'"]*)[^>]*>Comments</a>"#,
+    )
+    .unwrap();
+    let caps = re.captures(content)?;
+    return Some(DiscussionLinks {
+        article_url: url.clone(),
+        comments_url: caps["href"].to_string(),
+    });
+}
+
+/// Retrieve article url from tildes.net topic, item url
+/// points to the topic discussion page.
+fn get_tildes_links(url: &Url, content: &str) -> Option<DiscussionLinks> {
+    // Matching on:
+    // <p>Link URL: <a href="?(<url>")>
+    let re =
+        Regex::new(r#"<p>Link URL: <a[^>]*href\s?=['"]*(?<href>[^'"]*)[^>]*>"#)
+            .unwrap();
+    let article_url = match re.captures(content) {
+        Some(caps) => parse_http_link(&caps["href"])?,
+        None => url.clone(),
+    };
+    return Some(DiscussionLinks {
+        article_url,
+        comments_url: url.to_string(),
+    });
+}
+
+/// Retrieve comments url from Lemmy feed item, Lemmy exposes
+/// external link as item url and post url within the content.
+fn get_lemmy_links(url: &Url, content: &str) -> Option<DiscussionLinks> {
+    // Matching on:
+    // 12 points | <a href="https://<instance>/post/<id>">3 comments</a>
+    let re = Regex::new(
+        r#"\d+ points? \| <a[^>]*href\s?=['"]*(?<href>https?://[^'"]*/post/\d+)['"][^>]*>\d+ comments?</a>"#,
+    )
+    .unwrap();
+    let caps = re.captures(content)?;
+    return Some(DiscussionLinks {
+        article_url: url.clone(),
+        comments_url: caps["href"].to_string(),
+    });
+}
+
+/// Retrieve article url from Slashdot story, item url points
+/// to the discussion while article is linked within the summary.
+fn get_slashdot_links(url: &Url, content: &str) -> Option<DiscussionLinks> {
+    let re =
+        Regex::new(r#"<a[^>]*href\s?=['"]*(?<href>[^'"]*)[^>]*>"#).unwrap();
+    let article_url = re
+        .captures_iter(content)
+        .filter_map(|caps| parse_http_link(&caps["href"]))
+        .find(|u| {
+            let host = u.host_str().unwrap_or_default();
+            return !SLASHDOT_IGNORED_LINK_DOMAINS
+                .iter()
+                .any(|d| host.ends_with(d));
+        })
+        .unwrap_or(url.clone());
+    return Some(DiscussionLinks {
+        article_url,
+        comments_url: url.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const LOBSTERS_CONTENT: &str = r#"<article><p><a href="https://lobste.rs/s/ab12cd/some_story">Comments</a></p></article>"#;
+
+    const TILDES_CONTENT: &str = r#"<article><p>Link URL: <a href="https://example.com/article">https://example.com/article</a></p><p><a href="https://tildes.net/~tech/1abc/some_story">12 comments</a></p></article>"#;
+
+    const LEMMY_CONTENT: &str = r#"<article>submitted by <a href="https://lemmy.ml/u/someone">someone</a> to <a href="https://lemmy.ml/c/technology">technology</a><br>42 points | <a href="https://lemmy.ml/post/123456">7 comments</a><br><a href="https://example.com/article">https://example.com/article</a></article>"#;
+
+    const SLASHDOT_CONTENT: &str = r#"<article>An anonymous reader <a href="https://slashdot.org/~reader">writes</a>: <a href="https://example.com/article">The Example reports</a> that something happened.<p><div class="share_submission"><a href="https://twitter.com/share">Share</a></div></p><img src="https://feeds.feedburner.com/~r/Slashdot/slashdot/~4/abc"></article>"#;
+
+    const OLD_REDDIT_CONTENT: &str = r#"<article>submitted by <a href="https://old.reddit.com/user/someone"> /u/someone </a> <br/> <span><a href="https://example.com/article">[link]</a></span> <span><a href="https://old.reddit.com/r/rust/comments/abc/some_story/">[comments]</a></span></article>"#;
+
+    const HNRSS_CONTENT: &str = r#"<article><p>Article URL: <a href="https://example.com/article">https://example.com/article</a></p><p>Comments URL: <a href="https://news.ycombinator.com/item?id=123">https://news.ycombinator.com/item?id=123</a></p><p>Points: 231</p><p># Comments: 87</p></article>"#;
+
+    #[test]
+    fn test_retrieving_hnrss_org_metadata() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_discussion_metadata(
+            &url,
+            &"https://hnrss.org/frontpage".to_string(),
+            &HNRSS_CONTENT.to_string(),
+        );
+        assert_eq!(
+            Some(DiscussionMetadata {
+                score: Some(231),
+                comment_count: Some(87),
+                submitter: None,
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn test_retrieving_reddit_metadata() {
+        let url = Url::parse(
+            "https://old.reddit.com/r/rust/comments/abc/some_story/",
+        )
+        .unwrap();
+        let result = get_discussion_metadata(
+            &url,
+            &"https://old.reddit.com/r/rust/.rss".to_string(),
+            &OLD_REDDIT_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!(Some("someone".to_string()), result.submitter);
+        assert_eq!(None, result.score);
+    }
+
+    #[test]
+    fn test_retrieving_lemmy_metadata() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_discussion_metadata(
+            &url,
+            &"https://lemmy.ml/feeds/c/technology.xml".to_string(),
+            &LEMMY_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!(Some(42), result.score);
+        assert_eq!(Some(7), result.comment_count);
+        assert_eq!(Some("someone".to_string()), result.submitter);
+    }
+
+    #[test]
+    fn test_skipping_metadata_for_regular_feeds() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_discussion_metadata(
+            &url,
+            &"https://example.com/rss.xml".to_string(),
+            &"<article><p>Points: 10</p></article>".to_string(),
+        );
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_retrieving_lobsters_links() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://lobste.rs/rss".to_string(),
+            &LOBSTERS_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!(url, result.article_url);
+        assert_eq!(
+            "https://lobste.rs/s/ab12cd/some_story".to_string(),
+            result.comments_url
+        );
+        assert!(result.is_external());
+    }
+
+    #[test]
+    fn test_retrieving_lobsters_links_for_text_posts() {
+        let url = Url::parse("https://lobste.rs/s/ab12cd/some_story").unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://lobste.rs/rss".to_string(),
+            &LOBSTERS_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert!(!result.is_external());
+    }
+
+    #[test]
+    fn test_retrieving_tildes_links() {
+        let url =
+            Url::parse("https://tildes.net/~tech/1abc/some_story").unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://tildes.net/topics.atom".to_string(),
+            &TILDES_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!("https://example.com/article", result.article_url.as_str());
+        assert_eq!(url.to_string(), result.comments_url);
+        assert!(result.is_external());
+
+        let text_result = get_aggregator_links(
+            &url,
+            &"https://tildes.net/topics.atom".to_string(),
+            &"<article><p>Some text</p></article>".to_string(),
+        )
+        .unwrap();
+        assert!(!text_result.is_external());
+
+        let mailto_result = get_aggregator_links(
+            &url,
+            &"https://tildes.net/topics.atom".to_string(),
+            &"<p>Link URL: <a href=\"mailto:someone@example.com\">a</a></p>"
+                .to_string(),
+        );
+        assert!(mailto_result.is_none());
+    }
+
+    #[test]
+    fn test_retrieving_lemmy_links() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://lemmy.ml/feeds/c/technology.xml".to_string(),
+            &LEMMY_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!(url, result.article_url);
+        assert_eq!(
+            "https://lemmy.ml/post/123456".to_string(),
+            result.comments_url
+        );
+
+        let no_match = get_aggregator_links(
+            &url,
+            &"https://lemmy.ml/feeds/c/technology.xml".to_string(),
+            &"<article>Unrelated content</article>".to_string(),
+        );
+        assert!(no_match.is_none());
+
+        // Blogs publishing feeds under /feeds/ are not Lemmy instances.
+        let blog_feed =
+            Url::parse("https://blog.com/feeds/posts/default").unwrap();
+        assert!(!is_lemmy_feed(&blog_feed, &LEMMY_CONTENT.to_string()));
+        let other_instance =
+            Url::parse("https://lemmy.world/feeds/c/technology.xml").unwrap();
+        assert!(!is_lemmy_feed(&other_instance, &LEMMY_CONTENT.to_string()));
+        let feed = Url::parse("https://lemmy.ml/feeds/all.xml").unwrap();
+        assert!(is_lemmy_feed(&feed, &LEMMY_CONTENT.to_string()));
+    }
+
+    #[test]
+    fn test_retrieving_slashdot_links() {
+        let url = Url::parse(
+            "https://tech.slashdot.org/story/24/12/01/1234567/some-story",
+        )
+        .unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://rss.slashdot.org/Slashdot/slashdotMain".to_string(),
+            &SLASHDOT_CONTENT.to_string(),
+        )
+        .unwrap();
+        assert_eq!("https://example.com/article", result.article_url.as_str());
+        assert_eq!(url.to_string(), result.comments_url);
+    }
+
+    #[test]
+    fn test_skipping_unknown_aggregators() {
+        let url = Url::parse("https://example.com/article").unwrap();
+        let result = get_aggregator_links(
+            &url,
+            &"https://example.com/rss.xml".to_string(),
+            &LEMMY_CONTENT.to_string(),
+        );
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_retrieving_old_reddit_direct_link() {
+        let url = Url::parse(
+            "https://old.reddit.com/r/rust/comments/abc/some_story/",
+        )
+        .unwrap();
+        let result =
+            get_reddit_direct_link(&url, &OLD_REDDIT_CONTENT.to_string());
+        assert_eq!(
+            Some(Url::parse("https://example.com/article").unwrap()),
+            result
+        );
+    }
+
+    #[test]
+    fn test_retrieving_text_metadata() {
+        let text = "Der schnelle braune Fuchs springt über den faulen Hund, \
+            während die Katze gemütlich in der Sonne liegt und schläft. "
+            .repeat(30);
+        let meta = get_text_metadata(&text, 200);
+        assert_eq!(570, meta.word_count);
+        assert_eq!(3, meta.reading_time);
+        assert_eq!(Some("deu".to_string()), meta.language);
+
+        let meta = get_text_metadata("", 200);
+        assert_eq!(TextMetadata::default(), meta);
+    }
+
+    #[test]
+    fn test_generating_excerpts() {
+        let text = "First sentence here.  Second one!\nThird sentence is a bit longer? Fourth.";
+        assert_eq!("First sentence here. Second one!", get_excerpt(text, 50));
+        assert_eq!("First sentence here.", get_excerpt(text, 25));
+        assert_eq!("First…", get_excerpt(text, 12));
+        assert_eq!("Short text", get_excerpt("Short   text", 50));
+    }
+
+    #[test]
+    fn test_retrieving_lead_images() {
+        let base_url = Url::parse("https://test.com/articles/1").unwrap();
+        let html = r#"<html><head>
+            <meta name="twitter:image" content="https://test.com/twitter.png">
+            <meta content="/og.png" property="og:image" />
+            </head></html>"#;
+        assert_eq!(
+            Some("https://test.com/og.png".to_string()),
+            get_meta_image(html, &base_url)
+        );
+        assert_eq!(None, get_meta_image("<html></html>", &base_url));
+
+        let content = r#"<p>Text <img src="data:image/png;base64,AAA"><img alt="x" src='img/a.png'></p>"#;
+        assert_eq!(
+            Some("https://test.com/articles/img/a.png".to_string()),
+            get_first_image(content, &base_url)
+        );
+    }
+
+    fn product(text: &str) -> extractor::Product {
+        return extractor::Product {
+            title: String::new(),
+            content: format!("<p>{}</p>", text),
+            text: text.to_string(),
+        };
+    }
+
+    #[test]
+    fn test_selecting_content_candidates() {
+        let mut opts = Options::default();
+        opts.content_min_text_length = 20;
+        let article = "Some long enough article text.";
+        let (source, _) = select_content(
+            vec![
+                (
+                    ContentSource::Scraped,
+                    product("Please accept cookies to continue"),
+                ),
+                (ContentSource::Extracted, product(article)),
+                (ContentSource::Feed, product("Short")),
+            ],
+            &opts,
+        );
+        assert_eq!(ContentSource::Extracted, source);
+
+        let (source, p) = select_content(
+            vec![
+                (ContentSource::Scraped, product("Tiny")),
+                (ContentSource::Extracted, product("")),
+                (ContentSource::Feed, product("Short text")),
+            ],
+            &opts,
+        );
+        assert_eq!(ContentSource::Feed, source);
+        assert_eq!("Short text", p.text);
+
+        // Empty text is never acceptable, regardless of the
+        // configured minimum length.
+        opts.content_min_text_length = 0;
+        let (source, _) = select_content(
+            vec![
+                (ContentSource::Scraped, product("  ")),
+                (ContentSource::Feed, product("Short")),
+            ],
+            &opts,
+        );
+        assert_eq!(ContentSource::Feed, source);
+
+        let (source, _) = select_content(vec![], &opts);
+        assert_eq!(ContentSource::Feed, source);
+    }
+
+    #[test]
+    fn test_falling_back_to_feed_content() {
+        let opts = Options::default();
+        let client = HttpClient::init(&opts, true).unwrap();
+        let result = process_article_content(
+            &"https://example.com/article".to_string(),
+            &"https://example.com".to_string(),
+            &"https://example.com/rss".to_string(),
+            &mut "Link: <a href=\"https://example.com/article\">article</a>"
+                .to_string(),
+            &opts,
+            &client,
+            &ContentCache::default(),
+        )
+        .unwrap();
+        assert_eq!(ContentSource::Feed, result.content_source);
+        assert_eq!("Link: article", result.text);
+
+        // Invalid feed url prevents retrieving discussion
+        // metadata but not processing the content.
+        let result = process_article_content(
+            &"https://example.com/article".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            &mut "Some text".to_string(),
+            &opts,
+            &client,
+            &ContentCache::default(),
+        )
+        .unwrap();
+        assert_eq!(None, result.discussion);
+        assert_eq!("Some text", result.text);
+
+        // Links without host are processed without scraping.
+        let result = process_article_content(
+            &"mailto:someone@example.com".to_string(),
+            &"".to_string(),
+            &"exec:~/bin/feed.sh".to_string(),
+            &mut "Some text".to_string(),
+            &opts,
+            &client,
+            &ContentCache::default(),
+        )
+        .unwrap();
+        assert_eq!("Some text", result.text);
+    }
+
+    #[test]
+    fn test_stripping_html_tags() {
+        assert_eq!(
+            "Title Some text here",
+            strip_html_tags("<h1>Title</h1>\n<p>Some <b>text</b><br/>here</p>")
+        );
+    }
+}
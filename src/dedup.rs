@@ -0,0 +1,352 @@
+/// This module contains logic related to detecting articles
+/// published by multiple feeds (eg. blog post submitted to both
+/// HN and Lobsters) and collapsing them into single item.
+use log::info;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::Options;
+
+/// Maximum difference between article dates (in seconds) for
+/// articles to be considered duplicates based on title similarity.
+const TITLE_MATCH_MAX_DATE_DIFF: i64 = 3 * 24 * 60 * 60;
+/// Minimum length of normalised title used in similarity matching,
+/// short titles are too generic to be compared reliably.
+const TITLE_MATCH_MIN_LENGTH: usize = 16;
+
+/// Reference to duplicate of the article published by another feed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlsoIn {
+    /// Id of the feed containing the duplicate.
+    pub feed_id: String,
+    /// Display title of the feed containing the duplicate.
+    pub feed_title: String,
+    /// Url of the duplicate article.
+    pub url: String,
+    /// Link to the discussion associated with the duplicate.
+    pub comments_url: Option<String>,
+}
+
+/// Article data used when searching for duplicates.
+struct Entry {
+    feed_idx: usize,
+    item_idx: usize,
+    date: i64,
+    has_comments: bool,
+    also_in: AlsoIn,
+    guid: i64,
+}
+
+/// Detect duplicate articles across all the feeds, marking each
+/// article with list of the duplicates and assigning primary
+/// item for each group of duplicates.
+pub fn mark_duplicates(feeds: &[Arc<RefCell<Feed>>], opts: &Options) {
+    info!("Searching for duplicate articles");
+    let mut entries = Vec::new();
+    let mut keys: Vec<Option<String>> = Vec::new();
+    let mut titles: Vec<String> = Vec::new();
+    for (feed_idx, f) in feeds.iter().enumerate() {
+        let f = f.borrow();
+        for (item_idx, item) in f.items.iter().enumerate() {
+            keys.push(url_key(item.url()));
+            titles.push(normalize_title(item.title()));
+            entries.push(Entry {
+                feed_idx,
+                item_idx,
+                date: item.date(),
+                has_comments: item.comments_url().is_some(),
+                also_in: AlsoIn {
+                    feed_id: f.id().clone(),
+                    feed_title: f.display_title().clone(),
+                    url: item.url().clone(),
+                    comments_url: item.comments_url().clone(),
+                },
+                guid: item.guid(),
+            });
+        }
+    }
+
+    let mut groups = DisjointSet::new(entries.len());
+    let mut seen_urls: HashMap<&String, usize> = HashMap::new();
+    for (idx, key) in keys.iter().enumerate() {
+        if let Some(k) = key {
+            match seen_urls.get(k) {
+                Some(other) => groups.union(*other, idx),
+                None => _ = seen_urls.insert(k, idx),
+            }
+        }
+    }
+    if opts.dedup_title_similarity > 0 {
+        let threshold = opts.dedup_title_similarity as f64 / 100.0;
+        // Articles are compared only with the ones published within
+        // the date window, walking them in publication order.
+        let mut candidates: Vec<usize> = (0..entries.len())
+            .filter(|i| titles[*i].len() >= TITLE_MATCH_MIN_LENGTH)
+            .collect();
+        candidates.sort_by_key(|i| entries[*i].date);
+        let bigrams: HashMap<usize, HashSet<(char, char)>> = candidates
+            .iter()
+            .map(|i| (*i, title_bigrams(&titles[*i])))
+            .collect();
+        for (pos, i) in candidates.iter().enumerate() {
+            for j in &candidates[pos + 1..] {
+                if entries[*j].date - entries[*i].date
+                    > TITLE_MATCH_MAX_DATE_DIFF
+                {
+                    break;
+                }
+                if entries[*i].feed_idx == entries[*j].feed_idx {
+                    continue;
+                }
+                if dice_coefficient(&bigrams[i], &bigrams[j]) >= threshold {
+                    groups.union(*i, *j);
+                }
+            }
+        }
+    }
+
+    let mut grouped: HashMap<usize, Vec<usize>> = HashMap::new();
+    for idx in 0..entries.len() {
+        grouped.entry(groups.find(idx)).or_default().push(idx);
+    }
+    let mut count = 0;
+    for members in grouped.values().filter(|m| m.len() > 1) {
+        // Prefer source articles over aggregator submissions,
+        // falling back to the earliest one.
+        let primary = *members
+            .iter()
+            .min_by_key(|m| (entries[**m].has_comments, entries[**m].date, **m))
+            .unwrap();
+        for m in members {
+            let also_in = members
+                .iter()
+                .filter(|o| *o != m)
+                .map(|o| entries[*o].also_in.clone())
+                .collect();
+            let duplicate_of = match *m == primary {
+                true => None,
+                false => Some(entries[primary].guid),
+            };
+            let entry = &entries[*m];
+            feeds[entry.feed_idx].borrow_mut().items[entry.item_idx]
+                .set_duplicates(also_in, duplicate_of);
+        }
+        count += members.len() - 1;
+    }
+    info!("Found {} duplicate articles", count);
+}
+
+/// Collapse duplicate articles into single item, keeping the
+/// primary article if present. Order of the items is preserved.
+pub fn collapse_duplicates(items: Vec<FeedItem>) -> Vec<FeedItem> {
+    let mut result: Vec<FeedItem> = Vec::new();
+    let mut positions: HashMap<i64, usize> = HashMap::new();
+    for item in items {
+        let key = item.duplicate_of().unwrap_or(item.guid());
+        match positions.get(&key) {
+            Some(pos) => {
+                if item.duplicate_of().is_none() {
+                    result[*pos] = item;
+                }
+            }
+            None => {
+                positions.insert(key, result.len());
+                result.push(item);
+            }
+        }
+    }
+    return result;
+}
+
+/// Generate key used for matching articles by url, scheme,
+/// `www.` prefix, trailing slashes and fragments are ignored.
+fn url_key(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let mut key = format!(
+        "{}{}",
+        host.trim_start_matches("www."),
+        parsed.path().trim_end_matches('/')
+    );
+    if let Some(q) = parsed.query() {
+        key.push('?');
+        key.push_str(q);
+    }
+    return Some(key.to_lowercase());
+}
+
+/// Lowercase title and strip all non alphanumeric characters.
+fn normalize_title(title: &str) -> String {
+    return title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ");
+}
+
+/// Retrieve set of character bigrams for given title.
+fn title_bigrams(title: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = title.chars().collect();
+    return chars.windows(2).map(|w| (w[0], w[1])).collect();
+}
+
+/// Calculate Sørensen–Dice similarity coefficient of two bigram sets.
+fn dice_coefficient(
+    a: &HashSet<(char, char)>,
+    b: &HashSet<(char, char)>,
+) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let common = a.intersection(b).count();
+    return (2 * common) as f64 / (a.len() + b.len()) as f64;
+}
+
+/// Minimal union-find structure used for grouping duplicates.
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> DisjointSet {
+        return DisjointSet {
+            parents: (0..size).collect(),
+        };
+    }
+
+    fn find(&mut self, idx: usize) -> usize {
+        let mut root = idx;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = idx;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        return root;
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parents[rb] = ra;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn init_feed(url: &str, items: Vec<FeedItem>) -> Arc<RefCell<Feed>> {
+        let mut f =
+            Feed::init(url.to_string(), url.to_string(), url.to_string());
+        for i in items {
+            f.add_item(i);
+        }
+        return Arc::new(RefCell::new(f));
+    }
+
+    fn init_item(title: &str, url: &str, date: i64, guid: i64) -> FeedItem {
+        return FeedItem::new(title, url, "", "", date, false, "", guid);
+    }
+
+    #[test]
+    fn test_marking_duplicates_by_url() {
+        let mut hn_item = init_item("Post", "http://blog.com/post/", 200, 2);
+        hn_item.set_comments_url("https://news.ycombinator.com/1".to_string());
+        let feeds = vec![
+            init_feed("https://hnrss.org/frontpage", vec![hn_item]),
+            init_feed(
+                "https://blog.com/rss",
+                vec![
+                    init_item("Post", "https://www.blog.com/post", 300, 1),
+                    init_item("Other", "https://blog.com/other", 100, 3),
+                ],
+            ),
+        ];
+        mark_duplicates(&feeds, &Options::default());
+
+        let hn_item = &feeds[0].borrow().items[0];
+        let blog_item = &feeds[1].borrow().items[0];
+        assert_eq!(Some(1), hn_item.duplicate_of());
+        assert_eq!(None, blog_item.duplicate_of());
+        assert_eq!(1, hn_item.also_in().len());
+        assert_eq!("https://www.blog.com/post", hn_item.also_in()[0].url);
+        assert_eq!(
+            Some("https://news.ycombinator.com/1".to_string()),
+            blog_item.also_in()[0].comments_url
+        );
+        assert!(feeds[1].borrow().items[1].also_in().is_empty());
+
+        let collapsed = collapse_duplicates(vec![
+            hn_item.clone(),
+            feeds[1].borrow().items[1].clone(),
+            blog_item.clone(),
+        ]);
+        assert_eq!(2, collapsed.len());
+        assert_eq!(1, collapsed[0].guid());
+        assert_eq!(3, collapsed[1].guid());
+    }
+
+    #[test]
+    fn test_marking_duplicates_by_title() {
+        let feeds = vec![
+            init_feed(
+                "https://lobste.rs/rss",
+                vec![init_item(
+                    "Writing a compiler in Rust (2024)",
+                    "https://lobste.rs/s/abc",
+                    1000,
+                    1,
+                )],
+            ),
+            init_feed(
+                "https://blog.com/rss",
+                vec![
+                    init_item(
+                        "Writing a Compiler in Rust",
+                        "https://blog.com/compiler",
+                        2000,
+                        2,
+                    ),
+                    init_item(
+                        "Writing a compiler in Rust",
+                        "https://blog.com/compiler-old",
+                        2000 + TITLE_MATCH_MAX_DATE_DIFF * 2,
+                        3,
+                    ),
+                ],
+            ),
+        ];
+        let mut opts = Options::default();
+        mark_duplicates(&feeds, &opts);
+        assert!(feeds[0].borrow().items[0].also_in().is_empty());
+
+        opts.dedup_title_similarity = 80;
+        mark_duplicates(&feeds, &opts);
+        assert_eq!(1, feeds[0].borrow().items[0].also_in().len());
+        assert_eq!(Some(1), feeds[1].borrow().items[0].duplicate_of());
+        assert!(feeds[1].borrow().items[1].also_in().is_empty());
+    }
+
+    #[test]
+    fn test_generating_url_keys() {
+        assert_eq!(
+            Some("test.com/a/b?id=1".to_string()),
+            url_key("https://www.Test.com/a/b/?id=1#comments")
+        );
+        assert_eq!(None, url_key("not a url"));
+    }
+}
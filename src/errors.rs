@@ -26,10 +26,78 @@ pub enum UrlReaderError {
     MatcherError(String),
 }
 
+/// Errors returned by the HTTP client.
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("Fetching {0} is disallowed by robots.txt")]
+    DisallowedByRobots(String),
+
+    #[error("Request to {0} failed with status {1}")]
+    RequestFailed(String, u16),
+
+    #[error("Received non compatible content type: {0}")]
+    InvalidContentType(String),
+
+    #[error("Skipping request to {0}, network access disabled")]
+    Offline(String),
+}
+
+/// Errors returned when running external content plugins.
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("Plugin {0} timed out after {1}s")]
+    Timeout(String, u64),
+
+    #[error("Plugin {0} exited with status {1}")]
+    Failed(String, i32),
+
+    #[error("Plugin {0} returned invalid output: {1}")]
+    InvalidOutput(String, String),
+
+    #[error("Invalid plugin feed pattern `{0}`: {1}")]
+    InvalidPattern(String, String),
+}
+
+/// Errors returned when loading or running user scripts.
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("Could not read script {0}: {1}")]
+    Load(String, String),
+
+    #[error("Could not compile script {0}: {1}")]
+    Compile(String, String),
+
+    #[error("Script function {0} failed: {1}")]
+    Runtime(String, String),
+}
+
+/// Errors returned when compiling article tagging rules.
+#[derive(Error, Debug)]
+pub enum TaggingError {
+    #[error("Invalid tag rule filter `{0}`: {1}")]
+    InvalidFilter(String, String),
+
+    #[error("Invalid tag rule pattern `{0}`: {1}")]
+    InvalidPattern(String, String),
+
+    #[error("Tag rule for {0:?} does not define any conditions")]
+    MissingConditions(Vec<String>),
+}
+
+/// Errors returned when compiling article highlight rules.
+#[derive(Error, Debug)]
+pub enum HighlightError {
+    #[error("Invalid highlight filter `{0}`: {1}")]
+    InvalidFilter(String, String),
+}
+
 /// Errors related to invalid configuration settings passed
 /// by the user.
 #[derive(Error, Debug)]
 pub enum ConfigurationError {
     #[error("Invalid site URL defined in Liveboat options")]
     InvalidSiteUrl,
+
+    #[error("Invalid channel format `{0}`, expected `rss`, `atom` or `json`")]
+    InvalidChannelFormat(String),
 }
@@ -4,6 +4,7 @@ use std::fmt;
 
 use libnewsboat::matchable::Matchable;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use sha2::{Digest, Sha256};
 
 use crate::feed_item::FeedItem;
 
@@ -17,6 +18,9 @@ const MAX_TRUNCATED_FEED_ITEMS: usize = 50;
 /// MAX_TRUNCATED_FEED_ITEMS const.
 const TRUNCATED_FEED_ITEM_TIME_CUTOFF: i64 = 2;
 
+/// Number of hash characters appended to colliding tag feed ids.
+const TAG_HASH_LENGTH: usize = 6;
+
 /// Representation for single feed as retrieved from database.
 /// Used for storing both url and query based feeds.
 #[derive(Clone)]
@@ -86,6 +90,25 @@ impl Feed {
         }
     }
 
+    /// Initialize empty feed aggregating articles with given tag,
+    /// tag feeds are only used for generating channel documents.
+    /// If id derived from the tag is already in `taken_ids`
+    /// (eg. `C+` and `C#`) short hash of the tag is appended to it.
+    pub fn init_tag_feed(tag: &String, taken_ids: &[String]) -> Feed {
+        let slug: String = tag
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        let mut feed = Feed::init_query_feed(tag.clone(), 0);
+        feed.id = format!("tag-{}", slug);
+        if taken_ids.contains(&feed.id) {
+            let hash = format!("{:x}", Sha256::digest(tag.as_bytes()));
+            feed.id = format!("{}-{}", feed.id, &hash[..TAG_HASH_LENGTH]);
+        }
+        return feed;
+    }
+
     /// Add new article to the list of feed items.
     pub fn add_item(&mut self, item: FeedItem) {
         self.items.push(item)
@@ -116,6 +139,11 @@ impl Feed {
         return self.items.iter_mut().take(count);
     }
 
+    /// Iterate over all the feed articles.
+    pub fn items_mut(&mut self) -> impl Iterator<Item = &mut FeedItem> {
+        return self.items.iter_mut();
+    }
+
     /// Compact list of articles to either 50 or week max so
     /// that we dont have to load all the articles at the same time.
     pub fn truncate_items(&mut self) {
@@ -280,16 +308,17 @@ impl fmt::Debug for Feed {
 
 /// Compact version of the feed used for processing in feed lists.
 #[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FeedCompact {
     id: String,
     title: String,
     display_title: String,
     url: String,
-    feedlink: String,
-    hidden: bool,
+    feed_link: String,
+    is_hidden: bool,
     is_query: bool,
     tags: Vec<String>,
-    num_items: usize,
+    item_count: usize,
 }
 
 impl FeedCompact {
@@ -300,11 +329,11 @@ impl FeedCompact {
             title: f.title().clone(),
             display_title: f.display_title.clone(),
             url: f.url.clone(),
-            feedlink: f.feedlink.clone(),
-            hidden: f.hidden,
+            feed_link: f.feedlink.clone(),
+            is_hidden: f.hidden,
             is_query: f._is_query,
             tags: f.tags.clone(),
-            num_items: f.items.len(),
+            item_count: f.items.len(),
         };
     }
 }
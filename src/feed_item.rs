@@ -1,17 +1,25 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
 
+use atom_syndication::{
+    Category as AtomCategory, Content as AtomContent, Entry as AtomEntry,
+    FixedDateTime, Link as AtomLink, Person as AtomPerson, Text as AtomText,
+};
 use libnewsboat::matchable::Matchable;
 use rss::Item as RSSItem;
-use rss::{Category, Enclosure, ItemBuilder, Source};
+use rss::{Category, Enclosure, Guid, ItemBuilder, Source};
 use rusqlite::Error as SQLiteError;
 use rusqlite::Row;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
 use chrono::DateTime;
 
+use crate::content::{ContentSource, DiscussionMetadata, TextMetadata};
+use crate::dedup::AlsoIn;
 use crate::feed::Feed;
+use crate::highlight::Highlight;
 use crate::utils::now;
 
 /// Container for storing and operating
@@ -26,6 +34,9 @@ pub struct FeedItem {
     title: String,
     /// URL for the article.
     url: String,
+    /// URL for the article as defined in the source feed,
+    /// before any normalisation took place.
+    original_url: String,
     /// Author of the article.
     author: String,
     /// Timestamp of the article.
@@ -40,11 +51,24 @@ pub struct FeedItem {
     /// Raw content text of the article, will exclude
     /// any associated HTML tags.
     text: Option<String>,
+    /// Source of the article content (scraped page, extracted
+    /// or raw feed content).
+    content_source: ContentSource,
     /// Native Newsboat guid (id of the article in db)
     guid: i64,
     /// Length of the content (includes raw text of the article
     /// excluding tags)
     content_length: usize,
+    /// Number of words in the article text.
+    word_count: usize,
+    /// Estimated reading time of the article in minutes.
+    reading_time: usize,
+    /// ISO 639-3 code of the article language, if detected.
+    language: Option<String>,
+    /// Plain text excerpt of the article.
+    excerpt: Option<String>,
+    /// Url of the image representing the article.
+    lead_image: Option<String>,
     /// Optional link to comment site for given article.
     comments_url: Option<String>,
     /// Url to media associated with the article, eg. mp3 file,
@@ -52,8 +76,30 @@ pub struct FeedItem {
     enc_url: Option<String>,
     /// Mimetype of enclosure url associated with the article.
     enc_mime: Option<String>,
+    /// Size of the enclosure in bytes, if known.
+    enc_length: Option<u64>,
     /// unused at the moment
     flags: Option<String>,
+    /// Score of the submission as retrieved from
+    /// discussion aggregator feeds (eg. HN points).
+    score: Option<i64>,
+    /// Number of comments in the discussion associated
+    /// with the article.
+    comment_count: Option<i64>,
+    /// Name of the user who submitted the article
+    /// to discussion aggregator.
+    submitter: Option<String>,
+    /// List of duplicates of the article published by other feeds.
+    also_in: Vec<AlsoIn>,
+    /// Guid of the primary article if given article is a duplicate.
+    duplicate_of: Option<i64>,
+    /// Custom attributes set by plugins, exposed to query
+    /// feeds and included in the JSON output.
+    extra: BTreeMap<String, String>,
+    /// Article level tags assigned by tagging rules.
+    tags: Vec<String>,
+    /// Highlight styles of matching highlight rules.
+    highlights: Vec<Highlight>,
     /// Pointer of feed associated with given article,
     /// for query feeds will point to source url feed.
     pub feed_ptr: Option<Arc<RefCell<Feed>>>,
@@ -67,6 +113,7 @@ impl FeedItem {
             feed_url: row.get(0)?,
             title: row.get(2)?,
             url: row.get(3)?,
+            original_url: row.get(3)?,
             author: row.get(4)?,
             date: row.get(5)?,
             unread: row.get(6)?,
@@ -74,9 +121,24 @@ impl FeedItem {
             guid: row.get(8)?,
             enc_url: row.get(9)?,
             enc_mime: row.get(10)?,
+            enc_length: None,
             flags: row.get(11)?,
+            score: None,
+            comment_count: None,
+            submitter: None,
+            also_in: Vec::new(),
+            duplicate_of: None,
             content_length: 0,
+            word_count: 0,
+            reading_time: 0,
+            language: None,
+            excerpt: None,
+            lead_image: None,
+            extra: BTreeMap::new(),
+            tags: Vec::new(),
+            highlights: Vec::new(),
             text: None,
+            content_source: ContentSource::Feed,
             comments_url: None,
             feed_ptr: None,
         };
@@ -100,6 +162,11 @@ impl FeedItem {
         return &self.url;
     }
 
+    /// URL for the article as defined in the source feed.
+    pub fn original_url(&self) -> &String {
+        return &self.original_url;
+    }
+
     /// Author of the article.
     pub fn author(&self) -> &String {
         return &self.author;
@@ -168,17 +235,97 @@ impl FeedItem {
         return self.unread;
     }
 
+    /// Score of the submission as retrieved from
+    /// discussion aggregator feeds (eg. HN points).
+    pub fn score(&self) -> Option<i64> {
+        return self.score;
+    }
+
+    /// Number of comments in the discussion associated
+    /// with the article.
+    pub fn comment_count(&self) -> Option<i64> {
+        return self.comment_count;
+    }
+
+    /// Name of the user who submitted the article
+    /// to discussion aggregator.
+    pub fn submitter(&self) -> &Option<String> {
+        return &self.submitter;
+    }
+
     /// Url to media associated with the article, eg. mp3 file,
     /// youtube link etc.
     pub fn enc_url(&self) -> &Option<String> {
         return &self.enc_url;
     }
 
+    /// Custom attributes set by plugins.
+    pub fn extra(&self) -> &BTreeMap<String, String> {
+        return &self.extra;
+    }
+
+    /// Article level tags, not including tags of the feed.
+    pub fn tags(&self) -> &Vec<String> {
+        return &self.tags;
+    }
+
+    /// Highlight styles assigned to the article.
+    pub fn highlights(&self) -> &Vec<Highlight> {
+        return &self.highlights;
+    }
+
+    /// Article tags followed by tags of the associated feed.
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        if let Some(f) = &self.feed_ptr {
+            for tag in &f.borrow().tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        return tags;
+    }
+
+    /// ISO 639-3 code of the detected article language.
+    pub fn language(&self) -> &Option<String> {
+        return &self.language;
+    }
+
+    /// Source of the article content.
+    pub fn content_source(&self) -> ContentSource {
+        return self.content_source;
+    }
+
+    /// Plain text excerpt of the article.
+    pub fn excerpt(&self) -> &Option<String> {
+        return &self.excerpt;
+    }
+
+    /// Url of the image representing the article.
+    pub fn lead_image(&self) -> &Option<String> {
+        return &self.lead_image;
+    }
+
     /// Mimetype of enclosure url associated with the article.
     pub fn enc_mime(&self) -> &Option<String> {
         return &self.enc_mime;
     }
 
+    /// Size of the enclosure in bytes, if known.
+    pub fn enc_length(&self) -> Option<u64> {
+        return self.enc_length;
+    }
+
+    /// Whether or not article has audio or video enclosure.
+    pub fn has_media_enclosure(&self) -> bool {
+        let has_url = self.enc_url.as_ref().is_some_and(|u| !u.is_empty());
+        return has_url
+            && self.enc_mime.as_ref().is_some_and(|m| {
+                m.starts_with("audio/") || m.starts_with("video/")
+            });
+    }
+
     /// Set a pointer to feed associated with the article.
     pub fn set_ptr(&mut self, f_p: Arc<RefCell<Feed>>) {
         self.feed_ptr = Some(f_p)
@@ -188,10 +335,41 @@ impl FeedItem {
         self.content = content
     }
 
+    pub fn set_title(&mut self, title: String) {
+        self.title = title
+    }
+
+    /// Set custom article attribute.
+    pub fn set_extra(&mut self, key: String, value: String) {
+        self.extra.insert(key, value);
+    }
+
+    /// Add article tag, duplicate tags are ignored.
+    pub fn add_tag(&mut self, tag: String) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
     pub fn set_content_length(&mut self, size: usize) {
         self.content_length = size
     }
 
+    pub fn set_excerpt(&mut self, excerpt: String) {
+        self.excerpt = Some(excerpt)
+    }
+
+    pub fn set_lead_image(&mut self, url: String) {
+        self.lead_image = Some(url)
+    }
+
+    /// Update article with metadata derived from the article text.
+    pub fn set_text_metadata(&mut self, meta: TextMetadata) {
+        self.word_count = meta.word_count;
+        self.reading_time = meta.reading_time;
+        self.language = meta.language;
+    }
+
     pub fn set_comments_url(&mut self, url: String) {
         self.comments_url = Some(url)
     }
@@ -200,10 +378,18 @@ impl FeedItem {
         self.text = Some(text)
     }
 
+    pub fn set_content_source(&mut self, source: ContentSource) {
+        self.content_source = source
+    }
+
     pub fn set_url(&mut self, url: String) {
         self.url = url
     }
 
+    pub fn set_author(&mut self, author: String) {
+        self.author = author
+    }
+
     pub fn set_enc_url(&mut self, url: String) {
         self.enc_url = Some(url)
     }
@@ -212,6 +398,42 @@ impl FeedItem {
         self.enc_mime = Some(mime)
     }
 
+    pub fn set_enc_length(&mut self, length: u64) {
+        self.enc_length = Some(length)
+    }
+
+    /// Update article with discussion metadata retrieved
+    /// during content processing.
+    pub fn set_discussion_metadata(&mut self, meta: DiscussionMetadata) {
+        self.score = meta.score;
+        self.comment_count = meta.comment_count;
+        self.submitter = meta.submitter;
+    }
+
+    /// List of duplicates of the article published by other feeds.
+    pub fn also_in(&self) -> &Vec<AlsoIn> {
+        return &self.also_in;
+    }
+
+    /// Guid of the primary article if given article is a duplicate.
+    pub fn duplicate_of(&self) -> Option<i64> {
+        return self.duplicate_of;
+    }
+
+    /// Mark article as duplicated by other articles.
+    pub fn set_duplicates(
+        &mut self,
+        also_in: Vec<AlsoIn>,
+        duplicate_of: Option<i64>,
+    ) {
+        self.also_in = also_in;
+        self.duplicate_of = duplicate_of;
+    }
+
+    pub fn set_highlights(&mut self, highlights: Vec<Highlight>) {
+        self.highlights = highlights
+    }
+
     /// Convert date ts assigned to feed item to datetime string
     fn get_rfc_dt(&self) -> String {
         let dt = DateTime::from_timestamp(self.date, 0);
@@ -228,10 +450,19 @@ impl FeedItem {
             .link(self.url.clone())
             .author(self.author.clone())
             .pub_date(self.get_rfc_dt())
+            .comments(self.comments_url.clone())
+            .description(self.excerpt.clone())
             .build();
 
-        if include_content {
-            item.set_content(self.text.clone())
+        // Guid is derived from article url so that readers do not
+        // treat articles as new after every rebuild.
+        let guid = self.stable_id();
+        item.set_guid(Guid {
+            permalink: guid.starts_with("http"),
+            value: guid,
+        });
+        if include_content && !self.content.is_empty() {
+            item.set_content(self.content.clone())
         }
         if self.feed_ptr.is_some() {
             let f = self.feed_ptr.unwrap();
@@ -239,21 +470,41 @@ impl FeedItem {
                 title: Some(f.borrow().display_title().clone()),
                 url: f.borrow().feedlink().clone(),
             }));
+            let domain = match f.borrow().feedlink().is_empty() {
+                true => None,
+                false => Some(f.borrow().feedlink().clone()),
+            };
             let mut categories = Vec::new();
             for cat in f.borrow().tags.clone() {
                 categories.push(Category {
                     name: cat,
-                    // TODO
-                    domain: None,
+                    domain: domain.clone(),
                 })
             }
             item.set_categories(categories)
         }
+        if !self.tags.is_empty() {
+            let mut categories = item.categories().to_vec();
+            for tag in &self.tags {
+                if !categories.iter().any(|c| &c.name == tag) {
+                    categories.push(Category {
+                        name: tag.clone(),
+                        domain: None,
+                    });
+                }
+            }
+            item.set_categories(categories);
+        }
         if self.enc_url.is_some() {
             let enc_url = self.enc_url.unwrap();
             if enc_url.len() > 0 {
                 let mut enclosure = Enclosure::default();
                 enclosure.set_url(enc_url);
+                // Length is left empty if size of the enclosure
+                // is not known rather than reporting bogus value.
+                if let Some(length) = self.enc_length {
+                    enclosure.set_length(length.to_string());
+                }
                 if self.enc_mime.is_some() {
                     enclosure.set_mime_type(self.enc_mime.unwrap());
                 }
@@ -263,6 +514,77 @@ impl FeedItem {
         return item;
     }
 
+    /// Create new Atom entry based on feed item data.
+    pub fn to_atom_entry(self, include_content: bool) -> AtomEntry {
+        let date: FixedDateTime = DateTime::from_timestamp(self.date, 0)
+            .unwrap_or_default()
+            .into();
+        let mut entry = AtomEntry::default();
+        entry.set_id(self.stable_id());
+        entry.set_title(AtomText::plain(self.title.clone()));
+        entry.set_updated(date);
+        entry.set_published(Some(date));
+        if !self.author.is_empty() {
+            entry.set_authors(vec![AtomPerson {
+                name: self.author.clone(),
+                ..Default::default()
+            }]);
+        }
+        let mut links = vec![AtomLink {
+            href: self.url.clone(),
+            rel: "alternate".to_string(),
+            ..Default::default()
+        }];
+        if let Some(comments_url) = &self.comments_url {
+            links.push(AtomLink {
+                href: comments_url.clone(),
+                rel: "related".to_string(),
+                ..Default::default()
+            });
+        }
+        if let Some(enc_url) = self.enc_url.as_ref().filter(|u| !u.is_empty()) {
+            links.push(AtomLink {
+                href: enc_url.clone(),
+                rel: "enclosure".to_string(),
+                mime_type: self.enc_mime.clone(),
+                ..Default::default()
+            });
+        }
+        entry.set_links(links);
+        entry.set_categories(
+            self.all_tags()
+                .into_iter()
+                .map(|term| AtomCategory {
+                    term,
+                    ..Default::default()
+                })
+                .collect::<Vec<AtomCategory>>(),
+        );
+        if let Some(excerpt) = &self.excerpt {
+            entry.set_summary(Some(AtomText::plain(excerpt.clone())));
+        }
+        if include_content && !self.content.is_empty() {
+            entry.set_content(Some(AtomContent {
+                value: Some(self.content.clone()),
+                content_type: Some("html".to_string()),
+                ..Default::default()
+            }));
+        }
+        return entry;
+    }
+
+    /// Identifier of the article which stays the same between
+    /// builds, based on the article url as published by the feed.
+    pub fn stable_id(&self) -> String {
+        if !self.original_url.is_empty() {
+            return self.original_url.clone();
+        }
+        if !self.url.is_empty() {
+            return self.url.clone();
+        }
+        return format!("urn:liveboat:article:{}", self.guid);
+    }
+
     pub fn new(
         title: &str,
         url: &str,
@@ -276,6 +598,7 @@ impl FeedItem {
         return FeedItem {
             title: title.to_string(),
             url: url.to_string(),
+            original_url: url.to_string(),
             feed_url: feed_url.to_string(),
             author: author.to_string(),
             date: date,
@@ -284,11 +607,26 @@ impl FeedItem {
             guid: guid,
             enc_url: None,
             enc_mime: None,
+            enc_length: None,
             flags: None,
+            score: None,
+            comment_count: None,
+            submitter: None,
+            also_in: Vec::new(),
+            duplicate_of: None,
             text: None,
+            content_source: ContentSource::Feed,
             feed_ptr: None,
             comments_url: None,
             content_length: 0,
+            word_count: 0,
+            reading_time: 0,
+            language: None,
+            excerpt: None,
+            lead_image: None,
+            extra: BTreeMap::new(),
+            tags: Vec::new(),
+            highlights: Vec::new(),
         };
     }
 }
@@ -300,6 +638,7 @@ impl Matchable for FeedItem {
         match attr {
             "title" => Some(self.title.clone()),
             "link" => Some(self.url.clone()),
+            "original_link" => Some(self.original_url.clone()),
             "author" => Some(self.author.clone()),
             "unread" => {
                 let unread = if self.unread {
@@ -316,11 +655,20 @@ impl Matchable for FeedItem {
             "enclosure_url" => opt_attr_val(&self.enc_url),
             "enclosure_type" => opt_attr_val(&self.enc_mime),
             "flags" => opt_attr_val(&self.flags),
+            "score" => opt_num_attr_val(self.score),
+            "comment_count" => opt_num_attr_val(self.comment_count),
+            "submitter" => opt_attr_val(&self.submitter),
+            "word_count" => Some(format!("{}", self.word_count)),
+            "reading_time" => Some(format!("{}", self.reading_time)),
+            "language" => opt_attr_val(&self.language),
+            "article_tags" => Some(self.tags.join(" ")),
             // This index is generated by the newsboat when rendering article
             // list so we skip it during filtering.
             "articleindex" => Some(String::new()),
             _ => {
-                if let Some(feed) = &self.feed_ptr {
+                if let Some(value) = self.extra.get(attr) {
+                    Some(value.clone())
+                } else if let Some(feed) = &self.feed_ptr {
                     feed.borrow().attribute_value(attr)
                 } else {
                     None
@@ -355,25 +703,50 @@ fn opt_attr_val(attr: &Option<String>) -> Option<String> {
     return Some(String::new());
 }
 
+/// Utility used for processing Optional, numeric attrs, unset
+/// values are treated as 0 by the matcher.
+fn opt_num_attr_val(attr: Option<i64>) -> Option<String> {
+    if let Some(a) = attr {
+        return Some(format!("{}", a));
+    }
+    return Some(String::new());
+}
+
 impl Serialize for FeedItem {
     /// JSON serialization attributes.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("FeedItem", 12)?;
+        let mut state = serializer.serialize_struct("FeedItem", 28)?;
         state.serialize_field("title", &self.title)?;
         state.serialize_field("url", &self.url)?;
+        state.serialize_field("originalUrl", &self.original_url)?;
         state.serialize_field("date", &self.date)?;
         state.serialize_field("author", &self.author)?;
         state.serialize_field("guid", &self.guid)?;
         state.serialize_field("unread", &self.unread)?;
         state.serialize_field("content", &self.content)?;
         state.serialize_field("contentLength", &self.content_length)?;
+        state.serialize_field("contentSource", &self.content_source)?;
+        state.serialize_field("wordCount", &self.word_count)?;
+        state.serialize_field("readingTime", &self.reading_time)?;
+        state.serialize_field("language", &self.language)?;
+        state.serialize_field("excerpt", &self.excerpt)?;
+        state.serialize_field("leadImage", &self.lead_image)?;
+        state.serialize_field("extra", &self.extra)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("highlights", &self.highlights)?;
         state.serialize_field("flags", &self.flags)?;
         state.serialize_field("enclosureUrl", &self.enc_url)?;
         state.serialize_field("enclosureMime", &self.enc_mime)?;
+        state.serialize_field("enclosureLength", &self.enc_length)?;
         state.serialize_field("commentsUrl", &self.comments_url)?;
+        state.serialize_field("score", &self.score)?;
+        state.serialize_field("commentCount", &self.comment_count)?;
+        state.serialize_field("submitter", &self.submitter)?;
+        state.serialize_field("alsoIn", &self.also_in)?;
+        state.serialize_field("duplicateOf", &self.duplicate_of)?;
         state.end()
     }
 }
@@ -382,6 +755,7 @@ impl Serialize for FeedItem {
 mod tests {
 
     use super::*;
+    use libnewsboat::matcher::Matcher;
 
     #[test]
     fn test_matching_basic_feed_item_attrs() {
@@ -413,6 +787,26 @@ mod tests {
         assert_eq!(Some("1".to_string()), attr);
     }
 
+    #[test]
+    fn test_preserving_original_url() {
+        let mut item = FeedItem::new(
+            "item1",
+            "http://test.com/?utm_source=rss",
+            "",
+            "exaroth",
+            123456,
+            false,
+            "Test content",
+            1,
+        );
+        item.set_url("http://test.com/".to_string());
+        assert_eq!("http://test.com/", item.url());
+        assert_eq!(
+            Some("http://test.com/?utm_source=rss".to_string()),
+            item.attribute_value("original_link")
+        );
+    }
+
     #[test]
     fn test_matching_optional_feed_item_attrs() {
         let mut item = FeedItem::new(
@@ -442,6 +836,71 @@ mod tests {
         assert_eq!(Some("flag1 flag2".to_string()), attr);
     }
 
+    #[test]
+    fn test_matching_discussion_metadata_attrs() {
+        let mut item = FeedItem::new(
+            "item1",
+            "http://test.com",
+            "",
+            "exaroth",
+            123456,
+            false,
+            "Test content",
+            1,
+        );
+        let mut attr = item.attribute_value("score");
+        assert_eq!(Some("".to_string()), attr);
+        attr = item.attribute_value("comment_count");
+        assert_eq!(Some("".to_string()), attr);
+        item.set_discussion_metadata(DiscussionMetadata {
+            score: Some(231),
+            comment_count: Some(87),
+            submitter: Some("someone".to_string()),
+        });
+        attr = item.attribute_value("score");
+        assert_eq!(Some("231".to_string()), attr);
+        attr = item.attribute_value("comment_count");
+        assert_eq!(Some("87".to_string()), attr);
+        attr = item.attribute_value("submitter");
+        assert_eq!(Some("someone".to_string()), attr);
+
+        let matcher = Matcher::parse("score > 200").unwrap();
+        assert!(matcher.matches(&item).unwrap());
+        let matcher = Matcher::parse("score > 300").unwrap();
+        assert!(!matcher.matches(&item).unwrap());
+    }
+
+    #[test]
+    fn test_matching_text_metadata_attrs() {
+        let mut item = FeedItem::new(
+            "item1",
+            "http://test.com",
+            "",
+            "exaroth",
+            123456,
+            false,
+            "Test content",
+            1,
+        );
+        assert_eq!(Some("0".to_string()), item.attribute_value("word_count"));
+        assert_eq!(Some("".to_string()), item.attribute_value("language"));
+        item.set_text_metadata(TextMetadata {
+            word_count: 4000,
+            reading_time: 20,
+            language: Some("deu".to_string()),
+        });
+        assert_eq!(
+            Some("4000".to_string()),
+            item.attribute_value("word_count")
+        );
+
+        let matcher =
+            Matcher::parse("reading_time > 15 and language = \"deu\"").unwrap();
+        assert!(matcher.matches(&item).unwrap());
+        let matcher = Matcher::parse("reading_time < 15").unwrap();
+        assert!(!matcher.matches(&item).unwrap());
+    }
+
     #[test]
     fn test_falling_back_to_feed_attribute_if_not_found() {
         let f = Arc::new(RefCell::new(Feed::init(
@@ -469,6 +928,64 @@ mod tests {
         assert!(attr.is_none());
     }
 
+    #[test]
+    fn test_converting_to_rss_item() {
+        let mut f = Feed::init(
+            "http://feed.com".to_string(),
+            "Feed".to_string(),
+            "http://feedlink.com".to_string(),
+        );
+        f.tags.push("dev".to_string());
+        let f = Arc::new(RefCell::new(f));
+        let mut item = FeedItem::new(
+            "item1",
+            "http://test.com",
+            "",
+            "",
+            970000000,
+            false,
+            "<p>Content</p>",
+            1,
+        );
+        item.set_comments_url("http://comments.com".to_string());
+        item.set_excerpt("Content".to_string());
+        item.set_ptr(Arc::clone(&f));
+        let rss_item = item.clone().to_rss_item(true);
+        let guid = rss_item.guid().unwrap();
+        assert_eq!("http://test.com", guid.value());
+        assert!(guid.is_permalink());
+        assert_eq!(Some("Content"), rss_item.description());
+        assert_eq!(Some("<p>Content</p>"), rss_item.content());
+        assert_eq!(Some("http://comments.com"), rss_item.comments());
+        assert_eq!(
+            Some("http://feedlink.com"),
+            rss_item.categories()[0].domain()
+        );
+        assert!(item.clone().to_rss_item(false).content().is_none());
+
+        let mut item = item.clone();
+        item.set_enc_url("http://test.com/1.mp3".to_string());
+        assert_eq!(
+            "",
+            item.clone()
+                .to_rss_item(false)
+                .enclosure()
+                .unwrap()
+                .length()
+        );
+        item.set_enc_length(1234);
+        assert_eq!(
+            "1234",
+            item.to_rss_item(false).enclosure().unwrap().length()
+        );
+
+        let item = FeedItem::new("item2", "", "", "", 0, false, "", 2);
+        let rss_item = item.to_rss_item(false);
+        let guid = rss_item.guid().unwrap();
+        assert_eq!("urn:liveboat:article:2", guid.value());
+        assert!(!guid.is_permalink());
+    }
+
     #[test]
     fn test_retrieving_age_of_the_article() {
         let mut item = FeedItem::new(
@@ -7,6 +7,7 @@ use flate2::read::GzDecoder;
 use std::cmp::Ordering;
 use tar::Archive;
 
+use crate::http::HttpClient;
 use crate::template::{TemplateConfig, TEMPLATE_CONFIG_FNAME};
 use crate::utils;
 use anyhow::Result;
@@ -15,6 +16,7 @@ const TEMPLATES_ARCHIVE_FNAME: &str = "templates.tar.gz";
 
 /// Download and update local templates, taking versions in config.toml under consideration.
 pub fn fetch_templates(
+    client: &HttpClient,
     release_chan: &String,
     dl_path: &Path,
     tpl_dir: &Path,
@@ -28,7 +30,7 @@ pub fn fetch_templates(
     info!("Template download url: {}", t_url);
     let t_dl_path = dl_path.join(TEMPLATES_ARCHIVE_FNAME);
     info!("Local template download path is {}", t_dl_path.display());
-    utils::download_file(&t_url, &t_dl_path.as_path())?;
+    client.download_file(&t_url, &t_dl_path.as_path())?;
     let tar_gz = fs::File::open(&t_dl_path)?;
     let tar = GzDecoder::new(tar_gz);
     let mut archive = Archive::new(tar);
@@ -9,17 +9,33 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use console::Term;
+use url::Url;
 
 use crate::args::Args;
 use crate::builders::aux::Builder;
 use crate::builders::spa_builder::SinglePageBuilder;
-use crate::content::process_article_content;
+use crate::cache::ContentCache;
+use crate::content::{
+    get_discussion_metadata, get_excerpt, get_first_image, get_text_metadata,
+    process_article_content, strip_html_tags, ContentProcessingResult,
+    ContentSource, DiscussionMetadata,
+};
 use crate::db::{Connector, DBConnector};
+use crate::dedup::{collapse_duplicates, mark_duplicates};
 use crate::errors::FilesystemError;
 use crate::feed::Feed;
 use crate::feed_item::FeedItem;
+use crate::highlight::{parse_newsboat_highlights, Highlighter};
+use crate::http::HttpClient;
+use crate::links::LinkNormalizer;
+use crate::media::MediaMirror;
 use crate::opts::Options;
 use crate::paths::Paths;
+use crate::plugins::PluginRunner;
+use crate::podcast::EnclosureResolver;
+use crate::sanitize::Sanitizer;
+use crate::scripting::{ScriptHook, ScriptRunner};
+use crate::tagging::Tagger;
 use crate::template::{SimpleContext, TemplateConfig};
 use crate::urls::UrlReader;
 
@@ -32,6 +48,7 @@ pub struct BuildController {
     options: Options,
     url_reader: UrlReader,
     debug: bool,
+    offline: bool,
 }
 
 macro_rules! print_flush {
@@ -71,6 +88,7 @@ impl BuildController {
             options: opts,
             url_reader: url_reader,
             debug: args.debug,
+            offline: args.offline,
         };
         info!("Controller initialized");
         Ok(ctrl)
@@ -89,7 +107,85 @@ impl BuildController {
             self.get_feed_items(&db_connector, self.options.time_threshold)?;
         let feeds = self.get_url_feeds(&db_connector)?;
         self.populate_url_feeds(&feeds, &feed_items);
-        self.process_article_content(&feeds);
+        let scripts = self.get_script_runner()?;
+        if let Some(s) = &scripts {
+            s.run(ScriptHook::Load, &feeds);
+        }
+        let client = HttpClient::init(&self.options, self.offline)?;
+        let cache = ContentCache::load(&self.paths.content_cache_file());
+        info!("Loaded {} cached articles", cache.len());
+        let normalizer = match self.options.link_normalization {
+            true => Some(LinkNormalizer::init(&client, &self.options)),
+            false => None,
+        };
+        if let Some(n) = &normalizer {
+            self.normalize_article_links(&feeds, n);
+        }
+        let plugins = match self.options.plugins.is_empty() {
+            true => None,
+            false => Some(PluginRunner::init(&self.options)?),
+        };
+        let highlighter = self.get_highlighter()?;
+        let tagger = match self.options.tag_rules.is_empty() {
+            true => None,
+            false => Some(Tagger::init(&self.options)?),
+        };
+        let mirror = match self.options.mirror_images {
+            true => Some(MediaMirror::init(
+                &client,
+                self.paths.build_dir(),
+                &self.options,
+            )?),
+            false => None,
+        };
+        self.process_article_content(
+            &feeds,
+            &client,
+            &cache,
+            &normalizer,
+            &plugins,
+            &mirror,
+        );
+        if let Some(m) = &mirror {
+            m.finalize()?;
+        }
+        if let Some(s) = &scripts {
+            s.run(ScriptHook::Process, &feeds);
+        }
+        if let Some(t) = &tagger {
+            t.apply(&feeds);
+        }
+        if self.options.dedup_articles {
+            mark_duplicates(&feeds, &self.options);
+        }
+        if let Some(s) = &scripts {
+            s.run(ScriptHook::Render, &feeds);
+        }
+        // Scripts are free to modify the content, sanitization
+        // has to run after all of the content transformations.
+        if self.options.sanitize_content {
+            self.sanitize_article_content(&feeds);
+        }
+        if let Some(h) = &highlighter {
+            h.apply(&feeds);
+        }
+        if self.options.podcast_channels {
+            let resolver = EnclosureResolver::init(
+                &client,
+                &self.paths.enclosure_cache_file(),
+            );
+            resolver.resolve(&feeds);
+            resolver.save(&self.paths.enclosure_cache_file())?;
+        }
+        cache.prune(self.options.time_threshold);
+        cache.save(&self.paths.content_cache_file())?;
+        let skipped = client.skipped_requests();
+        if !skipped.is_empty() {
+            println!(
+                "Offline mode enabled, skipped {} network requests",
+                skipped.len()
+            );
+        }
         let q_feeds = self.get_query_feeds(&feeds)?;
         let tpl_config = TemplateConfig::get_config_for_template(
             self.paths.template_path(),
@@ -100,6 +196,7 @@ impl BuildController {
             &self.options,
             &tpl_config.template_settings,
             tpl_config.version.clone(),
+            skipped,
         );
         let builder = self.get_builder(&ctx)?;
         builder.create_tmp()?;
@@ -156,8 +253,18 @@ impl BuildController {
     }
 
     /// Process content of each url article, removing all extraneous elements
-    /// and scraping source data when required.
-    fn process_article_content(&self, feeds: &Vec<Arc<RefCell<Feed>>>) {
+    /// and scraping source data when required. Only articles included in
+    /// truncated feeds are scraped, metadata which does not require network
+    /// access is computed for all the articles.
+    fn process_article_content(
+        &self,
+        feeds: &Vec<Arc<RefCell<Feed>>>,
+        client: &HttpClient,
+        cache: &ContentCache,
+        normalizer: &Option<LinkNormalizer>,
+        plugins: &Option<PluginRunner>,
+        mirror: &Option<MediaMirror>,
+    ) {
         println!("Processing article content");
         for f in feeds {
             f.borrow_mut().sort_items();
@@ -166,43 +273,252 @@ impl BuildController {
             let feed_url = f.borrow().url().clone();
             let item_c = f.borrow().truncated_items_count();
             let mut count = 1;
-            for item in f.borrow_mut().truncated_iter() {
+            let mut dropped = Vec::new();
+            for (i, item) in f.borrow_mut().items_mut().enumerate() {
+                if i >= item_c {
+                    self.process_article_metadata(item, &feed_url, normalizer);
+                    continue;
+                }
                 print_flush!(
                     "Processing content for feed: {}, {}/{}",
                     title,
                     count,
                     item_c
                 );
+                if let Some(n) = normalizer {
+                    let url = n.normalize(item.url());
+                    item.set_url(url);
+                }
                 let res = process_article_content(
                     item.url(),
                     &feedlink,
                     &feed_url,
                     &mut item.content().clone(),
                     &self.options,
+                    client,
+                    cache,
                 );
-                if res.is_err() {
-                    info!(
-                        "Error processing content {}, {}",
-                        item.content(),
-                        res.unwrap_err()
-                    );
-                    item.set_content(String::new());
-                    continue;
+                let mut meta_image = None;
+                match res {
+                    Err(e) => {
+                        info!(
+                            "Error processing content {}, {}",
+                            item.content(),
+                            e
+                        );
+                        // Keep raw feed content rather than dropping it.
+                        let text = strip_html_tags(item.content());
+                        item.set_content_length(text.len());
+                        item.set_text(text);
+                        item.set_content_source(ContentSource::Feed);
+                    }
+                    Ok(content_processing_result) => {
+                        meta_image =
+                            content_processing_result.lead_image.clone();
+                        self.apply_content_processing_result(
+                            item,
+                            content_processing_result,
+                            normalizer,
+                        );
+                    }
                 }
-                let content_processing_result = res.unwrap();
-                item.set_content_length(
-                    content_processing_result.content_length(),
-                );
-                item.set_content(content_processing_result.content);
-                item.set_url(content_processing_result.url);
-                item.set_text(content_processing_result.text);
-                if content_processing_result.comments_url.is_some() {
-                    item.set_comments_url(
-                        content_processing_result.comments_url.unwrap(),
-                    )
+                if let Some(p) = plugins {
+                    if !p.process(item) {
+                        dropped.push(item.guid());
+                        count += 1;
+                        continue;
+                    }
+                }
+                // Lead image is selected before mirroring so that
+                // content images still point to the source urls.
+                if let Some(img) = self.get_lead_image(item, meta_image, mirror)
+                {
+                    item.set_lead_image(img);
+                }
+                if let (Some(m), Ok(base_url)) =
+                    (mirror, Url::parse(item.url()))
+                {
+                    let content = m.mirror_content(item.content(), &base_url);
+                    item.set_content(content);
                 }
+                self.assign_text_metadata(item);
                 count += 1
             }
+            if !dropped.is_empty() {
+                f.borrow_mut()
+                    .items
+                    .retain(|i| !dropped.contains(&i.guid()));
+            }
+        }
+    }
+
+    /// Compute metadata of the article which is not scraped, using
+    /// raw feed content and without issuing any network requests.
+    fn process_article_metadata(
+        &self,
+        item: &mut FeedItem,
+        feed_url: &str,
+        normalizer: &Option<LinkNormalizer>,
+    ) {
+        if let Some(n) = normalizer {
+            let url = n.normalize_local(item.url());
+            item.set_url(url);
+        }
+        let text = strip_html_tags(item.content());
+        item.set_content_length(text.len());
+        item.set_text(text);
+        item.set_content_source(ContentSource::Feed);
+        if let Ok(url) = Url::parse(item.url()) {
+            if let Some(meta) =
+                get_discussion_metadata(&url, feed_url, item.content())
+            {
+                self.assign_discussion_metadata(item, meta);
+            }
+        }
+        if let Some(img) = self.get_lead_image(item, None, &None) {
+            item.set_lead_image(img);
+        }
+        self.assign_text_metadata(item);
+    }
+
+    /// Assign excerpt, word count, reading time and language
+    /// based on the article text.
+    fn assign_text_metadata(&self, item: &mut FeedItem) {
+        let text = item.text().clone().unwrap_or_default();
+        if self.options.excerpt_length > 0 {
+            item.set_excerpt(get_excerpt(
+                &text,
+                self.options.excerpt_length as usize,
+            ));
+        }
+        item.set_text_metadata(get_text_metadata(
+            &text,
+            self.options.reading_speed_wpm,
+        ));
+    }
+
+    /// Assign discussion metadata to the article, aggregators
+    /// such as hnrss.org expose submitter as the article author.
+    fn assign_discussion_metadata(
+        &self,
+        item: &mut FeedItem,
+        mut meta: DiscussionMetadata,
+    ) {
+        if meta.submitter.is_none() && !item.author().is_empty() {
+            meta.submitter = Some(item.author().clone());
+        }
+        item.set_discussion_metadata(meta);
+    }
+
+    /// Assign results of content processing to the article.
+    fn apply_content_processing_result(
+        &self,
+        item: &mut FeedItem,
+        content_processing_result: ContentProcessingResult,
+        normalizer: &Option<LinkNormalizer>,
+    ) {
+        item.set_content_length(content_processing_result.content_length());
+        item.set_content(content_processing_result.content);
+        item.set_content_source(content_processing_result.content_source);
+        match normalizer {
+            // Links extracted from aggregator content
+            // might contain tracking parameters as well.
+            Some(n) => {
+                item.set_url(n.normalize_local(&content_processing_result.url))
+            }
+            None => item.set_url(content_processing_result.url),
+        }
+        item.set_text(content_processing_result.text);
+        if content_processing_result.comments_url.is_some() {
+            item.set_comments_url(
+                content_processing_result.comments_url.unwrap(),
+            )
+        }
+        if let Some(meta) = content_processing_result.discussion {
+            self.assign_discussion_metadata(item, meta);
+        }
+    }
+
+    /// Select lead image for the article, preferring image defined
+    /// in page metadata, then image enclosure and finally first
+    /// image included in the content.
+    fn get_lead_image(
+        &self,
+        item: &FeedItem,
+        meta_image: Option<String>,
+        mirror: &Option<MediaMirror>,
+    ) -> Option<String> {
+        let base_url = Url::parse(item.url()).ok()?;
+        let enc_image = match item.enc_mime() {
+            Some(mime) if mime.starts_with("image/") => item.enc_url().clone(),
+            _ => None,
+        };
+        let image = meta_image
+            .or(enc_image)
+            .or_else(|| get_first_image(item.content(), &base_url))?;
+        if let Some(m) = mirror {
+            return m.mirror_url(&image, &base_url).or(Some(image));
+        }
+        return Some(image);
+    }
+
+    /// Compile user scripts defined in options, if any.
+    fn get_script_runner(&self) -> Result<Option<ScriptRunner>> {
+        if self.options.scripts.is_empty() {
+            return Ok(None);
+        }
+        let paths = self
+            .options
+            .scripts
+            .iter()
+            .map(|s| self.paths.scripts_dir().join(s))
+            .collect();
+        return Ok(Some(ScriptRunner::init(&paths)?));
+    }
+
+    /// Initialize highlighter using rules defined in Newsboat config
+    /// (if enabled) followed by those defined in options.
+    fn get_highlighter(&self) -> Result<Option<Highlighter>> {
+        let mut rules = Vec::new();
+        let newsboat_config = self.paths.newsboat_config_file();
+        if self.options.newsboat_highlights && newsboat_config.is_file() {
+            rules =
+                parse_newsboat_highlights(&read_to_string(newsboat_config)?);
+            info!("Loaded {} Newsboat highlight rules", rules.len());
+        }
+        rules.extend(self.options.highlight_rules.iter().cloned());
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(Highlighter::init(&rules)?));
+    }
+
+    /// Normalise links of all the articles, original links
+    /// are retained by the article items. Redirects are resolved
+    /// later on only for articles with processed content.
+    fn normalize_article_links(
+        &self,
+        feeds: &Vec<Arc<RefCell<Feed>>>,
+        normalizer: &LinkNormalizer,
+    ) {
+        info!("Normalizing article links");
+        for f in feeds {
+            for item in f.borrow_mut().items_mut() {
+                let url = normalizer.normalize_local(item.url());
+                item.set_url(url);
+            }
+        }
+    }
+
+    /// Apply sanitization policy to content of all the articles.
+    fn sanitize_article_content(&self, feeds: &Vec<Arc<RefCell<Feed>>>) {
+        info!("Sanitizing article content");
+        let sanitizer = Sanitizer::init(&self.options);
+        for f in feeds {
+            for item in f.borrow_mut().items_mut() {
+                let content = sanitizer.sanitize(item.content());
+                item.set_content(content);
+            }
         }
     }
 
@@ -243,6 +559,7 @@ impl BuildController {
         for query_f in query_feeds {
             let mut q =
                 Feed::init_query_feed(query_f.title.clone(), query_f.line_no);
+            let mut matched = Vec::new();
             for f in feeds {
                 for i in &f.borrow().items {
                     match query_f.matcher.matches(i) {
@@ -257,12 +574,15 @@ impl BuildController {
                                     query_f.title,
                                     i
                                 );
-                                q.add_item(i.clone())
+                                matched.push(i.clone())
                             }
                         }
                     }
                 }
             }
+            for i in collapse_duplicates(matched) {
+                q.add_item(i)
+            }
             q.sort_items();
             q.tags = query_f.get_tags()?;
             result.push(q)
@@ -285,6 +605,8 @@ impl BuildController {
 mod tests {
 
     use super::*;
+    use crate::opts::PluginConfig;
+    use libnewsboat::matchable::Matchable;
 
     #[test]
     fn test_processing_query_feeds_simple() {
@@ -297,6 +619,7 @@ mod tests {
             paths: Paths::default(),
             options: Options::default(),
             debug: false,
+            offline: false,
         };
 
         let f1 = Arc::new(RefCell::new(Feed::init(
@@ -472,6 +795,7 @@ mod tests {
             paths: Paths::default(),
             options: Options::default(),
             debug: false,
+            offline: false,
         };
 
         let result = ctrl.get_query_feeds(&feeds).unwrap();
@@ -496,6 +820,7 @@ mod tests {
             paths: Paths::default(),
             options: Options::default(),
             debug: false,
+            offline: false,
         };
 
         let f1 = Arc::new(RefCell::new(Feed::init(
@@ -570,6 +895,7 @@ mod tests {
             paths: Paths::default(),
             options: Options::default(),
             debug: false,
+            offline: false,
         };
 
         let f1 = Arc::new(RefCell::new(Feed::init(
@@ -643,6 +969,7 @@ mod tests {
             paths: Paths::default(),
             options: opts,
             debug: false,
+            offline: false,
         };
 
         let f1 = Arc::new(RefCell::new(Feed::init(
@@ -695,6 +1022,7 @@ http://feed3.com
             paths: Paths::default(),
             options: Options::default(),
             debug: false,
+            offline: false,
         };
 
         let mut db_mock = MockConnector::new();
@@ -726,4 +1054,98 @@ http://feed3.com
         assert_eq!("Some feed 2", feeds[1].borrow().display_title());
         assert_eq!("Feed3", feeds[2].borrow().title());
     }
+
+    #[test]
+    fn test_running_plugins_for_unprocessed_content() {
+        let mut options = Options::default();
+        options.plugins = vec![PluginConfig {
+            command: r#"cat > /dev/null; echo '{"drop": true}'"#.to_string(),
+            domains: Vec::new(),
+            feed_patterns: vec![".*".to_string()],
+            timeout: 5,
+        }];
+        let ctrl = BuildController {
+            url_reader: UrlReader::init("".to_string()),
+            paths: Paths::default(),
+            options,
+            debug: false,
+            offline: true,
+        };
+        let f = Arc::new(RefCell::new(Feed::init(
+            "".to_string(),
+            "Feed".to_string(),
+            "".to_string(),
+        )));
+        let mut item = FeedItem::new(
+            "Item",
+            "not a url",
+            "",
+            "",
+            1733974974,
+            true,
+            "<p>Text</p>",
+            1,
+        );
+        item.set_ptr(Arc::clone(&f));
+        f.borrow_mut().add_item(item);
+
+        let client = HttpClient::init(&ctrl.options, true).unwrap();
+        let cache = ContentCache::default();
+        let runner = PluginRunner::init(&ctrl.options).unwrap();
+        ctrl.process_article_content(
+            &vec![Arc::clone(&f)],
+            &client,
+            &cache,
+            &None,
+            &Some(runner),
+            &None,
+        );
+        assert_eq!(0, f.borrow().items.len());
+    }
+
+    #[test]
+    fn test_computing_metadata_for_articles_outside_truncated_feed() {
+        let ctrl = BuildController {
+            url_reader: UrlReader::init("".to_string()),
+            paths: Paths::default(),
+            options: Options::default(),
+            debug: false,
+            offline: true,
+        };
+        let f = Arc::new(RefCell::new(Feed::init(
+            "https://hnrss.org/frontpage".to_string(),
+            "Feed".to_string(),
+            "".to_string(),
+        )));
+        for i in 0..60 {
+            let mut item = FeedItem::new(
+                "Item",
+                &format!("https://example.com/{}", i),
+                "someone",
+                "",
+                1733974974 - i * 86400,
+                true,
+                "<p>Some article text</p><p>Points: 12</p>",
+                1,
+            );
+            item.set_ptr(Arc::clone(&f));
+            f.borrow_mut().add_item(item);
+        }
+        assert!(f.borrow_mut().truncated_items_count() < 60);
+
+        let client = HttpClient::init(&ctrl.options, true).unwrap();
+        ctrl.process_article_content(
+            &vec![Arc::clone(&f)],
+            &client,
+            &ContentCache::default(),
+            &None,
+            &None,
+            &None,
+        );
+        let feed = f.borrow();
+        let item = feed.items.last().unwrap();
+        assert_eq!(Some("5".to_string()), item.attribute_value("word_count"));
+        assert_eq!(Some("12".to_string()), item.attribute_value("score"));
+        assert!(item.excerpt().is_some());
+    }
 }
@@ -8,6 +8,7 @@ use crate::handlers::aux;
 use crate::handlers::{
     NIGHTLY_CHANNEL_NAME, RELEASE_CHANNEL, STABLE_CHANNEL_NAME,
 };
+use crate::http::HttpClient;
 use crate::opts::Options;
 use crate::paths::Paths;
 
@@ -46,7 +47,9 @@ pub fn cold_start(use_nightly: bool, paths: &Paths) -> Result<()> {
     }
     println!("Using {} as release channel", release_channel);
 
+    let client = HttpClient::init(&opts, false)?;
     aux::fetch_templates(
+        &client,
         &release_channel,
         paths.tmp_dir().join("update").as_path(),
         paths.template_dir(),
@@ -16,6 +16,8 @@ use crate::handlers::LIVEBOAT_UPDATE_BIN_PATH_ENV;
 use crate::handlers::{
     NIGHTLY_CHANNEL_NAME, RELEASE_CHANNEL, STABLE_CHANNEL_NAME,
 };
+use crate::http::HttpClient;
+use crate::opts::Options;
 use crate::paths::Paths;
 use crate::utils;
 
@@ -46,6 +48,7 @@ pub fn update_files(
     }
     let dl_path = paths.tmp_dir().join("update");
     fs::create_dir_all(&dl_path)?;
+    let client = HttpClient::init(&Options::init(paths.config_file())?, false)?;
 
     let release_channel: String;
     match use_nightly {
@@ -63,19 +66,24 @@ pub fn update_files(
 
     let mut restart_required = false;
     let new_version_available = check_newer_binary_version_available(
+        &client,
         &release_channel,
         dl_path.as_path(),
     )?;
     match new_version_available {
         true => {
             println!("Newer version of Liveboat found. Fetching...");
-            restart_required =
-                update_liveboat_binary(&release_channel, dl_path.as_path())?;
+            restart_required = update_liveboat_binary(
+                &client,
+                &release_channel,
+                dl_path.as_path(),
+            )?;
         }
         false => {
             if debug {
                 println!("Debug mode enabled, forcing redownload...");
                 restart_required = update_liveboat_binary(
+                    &client,
                     &release_channel,
                     dl_path.as_path(),
                 )?;
@@ -85,6 +93,7 @@ pub fn update_files(
         }
     }
     aux::fetch_templates(
+        &client,
         &release_channel,
         dl_path.as_path(),
         paths.template_dir(),
@@ -98,6 +107,7 @@ pub fn update_files(
 /// Download and update local liveboat binary. We return bool in result
 /// indicating whether to attempt to propagate to sudo in order to replace the binary.
 fn update_liveboat_binary(
+    client: &HttpClient,
     release_chan: &String,
     dl_path: &Path,
 ) -> Result<bool> {
@@ -119,7 +129,7 @@ fn update_liveboat_binary(
     info!("Download url for binary is {}", d_url);
     let d_dl_path = dl_path.join(LIVEBOAT_FNAME);
     info!("Download path for binary is {}", d_dl_path.display());
-    utils::download_file(&d_url, &d_dl_path.as_path())?;
+    client.download_file(&d_url, &d_dl_path.as_path())?;
     let exe_path = current_exe()?;
     fs::set_permissions(&d_dl_path, fs::Permissions::from_mode(0o755))?;
     info!("Copying binary to {}", exe_path.display());
@@ -137,6 +147,7 @@ fn update_liveboat_binary(
 /// Fetch and compare VERSION file in the latest release channel and
 /// compare against local version.
 fn check_newer_binary_version_available(
+    client: &HttpClient,
     release_chan: &String,
     dl_path: &Path,
 ) -> Result<bool> {
@@ -145,7 +156,7 @@ fn check_newer_binary_version_available(
     info!("Remote url for VERSION file is {}", v_url);
     let v_dl_path = dl_path.join(VERSION_FNAME);
     info!("Local download path is {}", v_dl_path.display());
-    utils::download_file(&v_url, &v_dl_path.as_path())?;
+    client.download_file(&v_url, &v_dl_path.as_path())?;
     let v_contents = read_to_string(v_dl_path)?;
     info!("Raw contents of VERSION is {}", v_contents);
     let v_contents = v_contents
@@ -0,0 +1,203 @@
+/// This module contains logic related to highlighting articles
+/// based on Newsboat `highlight-article` rules, allowing templates
+/// to flag the same articles as the terminal client.
+use log::{info, warn};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use anyhow::Result;
+use libnewsboat::matcher::Matcher;
+use libnewsboat::utils as libutils;
+use serde::Serialize;
+
+use crate::errors::HighlightError;
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::HighlightRule;
+
+/// Name of the Newsboat config directive defining article highlights.
+const HIGHLIGHT_ARTICLE_DIRECTIVE: &str = "highlight-article";
+
+/// Highlight style assigned to the article.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Highlight {
+    /// Name of the highlight, if defined in Liveboat options.
+    pub name: Option<String>,
+    /// Foreground color, either Newsboat color name or hex value.
+    pub foreground: String,
+    /// Background color, either Newsboat color name or hex value.
+    pub background: String,
+    /// Additional text attributes, eg. `bold`, `underline`.
+    pub attributes: Vec<String>,
+}
+
+impl From<&HighlightRule> for Highlight {
+    fn from(rule: &HighlightRule) -> Highlight {
+        return Highlight {
+            name: rule.name.clone(),
+            foreground: rule.foreground.clone(),
+            background: rule.background.clone(),
+            attributes: rule.attributes.clone(),
+        };
+    }
+}
+
+/// Highlighter assigning highlight styles to matching articles.
+pub struct Highlighter {
+    rules: Vec<(Matcher, Highlight)>,
+}
+
+impl Highlighter {
+    /// Initialize highlighter, compiling filters of given rules.
+    pub fn init(rules: &Vec<HighlightRule>) -> Result<Highlighter> {
+        let mut compiled = Vec::new();
+        for rule in rules {
+            let matcher = Matcher::parse(&rule.filter).map_err(|e| {
+                HighlightError::InvalidFilter(rule.filter.clone(), e)
+            })?;
+            compiled.push((matcher, Highlight::from(rule)));
+        }
+        return Ok(Highlighter { rules: compiled });
+    }
+
+    /// Assign highlights to all the articles, in the order
+    /// the rules were defined.
+    pub fn apply(&self, feeds: &Vec<Arc<RefCell<Feed>>>) {
+        info!("Assigning article highlights");
+        for f in feeds {
+            // Matcher accesses feed attributes via article feed
+            // pointer so matching is done on immutable borrow first.
+            let highlights: Vec<Vec<Highlight>> = f
+                .borrow()
+                .items
+                .iter()
+                .map(|i| self.get_highlights(i))
+                .collect();
+            for (item, h) in f.borrow_mut().items_mut().zip(highlights) {
+                item.set_highlights(h);
+            }
+        }
+    }
+
+    /// Retrieve highlights of all the rules matching the article.
+    fn get_highlights(&self, item: &FeedItem) -> Vec<Highlight> {
+        let mut result = Vec::new();
+        for (matcher, highlight) in &self.rules {
+            match matcher.matches(item) {
+                Ok(true) => result.push(highlight.clone()),
+                Ok(false) => (),
+                Err(e) => warn!("Matcher error: {:?}", e),
+            }
+        }
+        return result;
+    }
+}
+
+/// Parse `highlight-article` directives from Newsboat config
+/// file contents, eg.
+/// `highlight-article "title =~ \"rust\"" green default bold`.
+pub fn parse_newsboat_highlights(config: &str) -> Vec<HighlightRule> {
+    let mut rules = Vec::new();
+    for line in config.lines() {
+        let tokens = libutils::tokenize_quoted(line, " \r\n\t");
+        if tokens.first().map(|t| t.as_str())
+            != Some(HIGHLIGHT_ARTICLE_DIRECTIVE)
+        {
+            continue;
+        }
+        if tokens.len() < 4 {
+            warn!("Skipping invalid highlight directive: {}", line);
+            continue;
+        }
+        rules.push(HighlightRule {
+            filter: tokens[1].clone(),
+            name: None,
+            foreground: tokens[2].clone(),
+            background: tokens[3].clone(),
+            attributes: tokens[4..].to_vec(),
+        });
+    }
+    return rules;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parsing_newsboat_highlights() {
+        let config = r#"
+# highlight-article "title =~ \"commented\"" red default
+auto-reload yes
+highlight-article "title =~ \"[Rr]ust\" and unread = \"yes\"" green default bold underline # inline
+highlight-article "age > 30" color8 default
+highlight-article "invalid"
+highlight article cyan default
+"#;
+        let rules = parse_newsboat_highlights(config);
+        assert_eq!(2, rules.len());
+        assert_eq!(
+            "title =~ \"[Rr]ust\" and unread = \"yes\"",
+            rules[0].filter
+        );
+        assert_eq!("green", rules[0].foreground);
+        assert_eq!(vec!["bold", "underline"], rules[0].attributes);
+        assert_eq!("age > 30", rules[1].filter);
+        assert_eq!("color8", rules[1].foreground);
+        assert!(rules[1].attributes.is_empty());
+    }
+
+    #[test]
+    fn test_highlighting_articles() {
+        let f = Arc::new(RefCell::new(Feed::init(
+            "https://blog.com/rss".to_string(),
+            "Blog".to_string(),
+            "https://blog.com".to_string(),
+        )));
+        for (guid, title) in [(1, "Rust 1.80"), (2, "Cooking")] {
+            let mut item = FeedItem::new(
+                title,
+                "",
+                "https://blog.com/rss",
+                "",
+                0,
+                true,
+                "",
+                guid,
+            );
+            item.set_ptr(Arc::clone(&f));
+            f.borrow_mut().add_item(item);
+        }
+        let mut rules = parse_newsboat_highlights(
+            r#"highlight-article "title =~ \"Rust\"" green default bold"#,
+        );
+        rules.push(HighlightRule {
+            filter: "rssurl =~ \"blog.com\"".to_string(),
+            name: Some("blog".to_string()),
+            foreground: "#ff0000".to_string(),
+            background: "default".to_string(),
+            attributes: Vec::new(),
+        });
+        let highlighter = Highlighter::init(&rules).unwrap();
+        let feeds = vec![f];
+        highlighter.apply(&feeds);
+
+        let f = feeds[0].borrow();
+        assert_eq!(2, f.items[0].highlights().len());
+        assert_eq!("green", f.items[0].highlights()[0].foreground);
+        assert_eq!(
+            vec![Highlight {
+                name: Some("blog".to_string()),
+                foreground: "#ff0000".to_string(),
+                background: "default".to_string(),
+                attributes: Vec::new(),
+            }],
+            *f.items[1].highlights()
+        );
+
+        rules[0].filter = "title =~".to_string();
+        assert!(Highlighter::init(&rules).is_err());
+    }
+}
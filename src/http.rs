@@ -0,0 +1,369 @@
+/// This module contains shared HTTP client used for scraping
+/// article content and downloading release files.
+use log::info;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{read, File};
+use std::io::copy as ioCopy;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use reqwest::blocking::{Client, ClientBuilder, Response};
+use reqwest::ClientBuilder as AsyncClientBuilder;
+use reqwest::{Certificate, Method, Proxy, StatusCode};
+use url::Url;
+
+use crate::errors::HttpError;
+use crate::opts::Options;
+
+/// Product token used when matching robots.txt user agent groups.
+const ROBOTS_AGENT_TOKEN: &str = "liveboat";
+
+/// Default user agent sent with every request.
+pub fn default_user_agent() -> String {
+    format!(
+        "liveboat/{} (+https://github.com/exaroth/liveboat)",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Representation of robots.txt rules applicable to Liveboat.
+#[derive(Debug, Default, Clone)]
+struct RobotsRules {
+    /// List of (path pattern, allowed) pairs.
+    rules: Vec<(String, bool)>,
+}
+
+/// Check if path matches robots.txt rule, `*` matches any sequence
+/// of characters and trailing `$` anchors the rule at the end of the
+/// path, otherwise rules are matched as path prefixes.
+fn matches_robots_rule(rule: &str, path: &str) -> bool {
+    let (rule, anchored) = match rule.strip_suffix('$') {
+        Some(r) => (r, true),
+        None => (rule, false),
+    };
+    let mut parts = rule.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (idx, part) in parts.iter().enumerate() {
+        if anchored && idx == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    return !anchored || rest.is_empty();
+}
+
+impl RobotsRules {
+    /// Parse robots.txt contents, retaining only rules from the group
+    /// matching Liveboat user agent or wildcard group if not found.
+    fn parse(raw: &str) -> RobotsRules {
+        let mut agent_rules: Vec<(String, bool)> = Vec::new();
+        let mut wildcard_rules: Vec<(String, bool)> = Vec::new();
+        let mut agent_matched = false;
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        for line in raw.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
+                    }
+                    group_agents.push(value.to_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // Empty disallow means everything is allowed.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (value.to_string(), key == "allow");
+                    if group_agents.iter().any(|a| a == ROBOTS_AGENT_TOKEN) {
+                        agent_matched = true;
+                        agent_rules.push(rule);
+                    } else if group_agents.iter().any(|a| a == "*") {
+                        wildcard_rules.push(rule);
+                    }
+                }
+                _ => (),
+            }
+        }
+        if agent_matched {
+            return RobotsRules { rules: agent_rules };
+        }
+        return RobotsRules {
+            rules: wildcard_rules,
+        };
+    }
+
+    /// Check if path is allowed, longest matching rule wins.
+    fn is_allowed(&self, path: &str) -> bool {
+        let mut result = true;
+        let mut longest = 0;
+        for (rule, allowed) in &self.rules {
+            if matches_robots_rule(rule, path) && rule.len() >= longest {
+                longest = rule.len();
+                result = *allowed;
+            }
+        }
+        return result;
+    }
+}
+
+/// Shared HTTP client handling timeouts, retries, request
+/// spacing per domain and (optionally) robots.txt rules.
+#[derive(Debug)]
+pub struct HttpClient {
+    client: Client,
+    max_retries: u64,
+    retry_backoff: Duration,
+    domain_delay: Duration,
+    respect_robots_txt: bool,
+    /// Whether or not network access is disabled.
+    offline: bool,
+    /// List of urls for which requests were skipped in offline mode.
+    skipped: RefCell<Vec<String>>,
+    /// Time of the last request issued for given host.
+    last_request: RefCell<HashMap<String, Instant>>,
+    /// Robots.txt rules cached for given host.
+    robots: RefCell<HashMap<String, RobotsRules>>,
+}
+
+impl HttpClient {
+    /// Initialize new client using settings defined in options,
+    /// offline clients will refuse to issue any requests.
+    /// Read timeout applies to every read operation rather than
+    /// the whole request so that large downloads are not interrupted.
+    pub fn init(opts: &Options, offline: bool) -> Result<HttpClient> {
+        let mut builder = AsyncClientBuilder::new()
+            .user_agent(opts.http_user_agent.clone())
+            .connect_timeout(Duration::from_secs(opts.http_connect_timeout))
+            .read_timeout(Duration::from_secs(opts.http_read_timeout));
+        if !opts.http_proxy.is_empty() {
+            builder = builder.proxy(Proxy::all(opts.http_proxy.as_str())?);
+        }
+        if !opts.http_ca_bundle.is_empty() {
+            let raw = read(&opts.http_ca_bundle)?;
+            for cert in Certificate::from_pem_bundle(&raw)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        // Blocking client applies 30s total timeout unless disabled.
+        let builder = ClientBuilder::from(builder).timeout(None);
+        return Ok(HttpClient {
+            client: builder.build()?,
+            max_retries: opts.http_max_retries,
+            retry_backoff: Duration::from_millis(opts.http_retry_backoff_ms),
+            domain_delay: Duration::from_millis(opts.http_domain_delay_ms),
+            respect_robots_txt: opts.http_respect_robots_txt,
+            offline,
+            skipped: RefCell::new(Vec::new()),
+            last_request: RefCell::new(HashMap::new()),
+            robots: RefCell::new(HashMap::new()),
+        });
+    }
+
+    /// Issue GET request, retrying on network errors and
+    /// server side failures.
+    pub fn get(&self, url: &str) -> Result<Response> {
+        return self.request(Method::GET, url);
+    }
+
+    /// Issue HEAD request, retrying on network errors and
+    /// server side failures.
+    pub fn head(&self, url: &str) -> Result<Response> {
+        return self.request(Method::HEAD, url);
+    }
+
+    fn request(&self, method: Method, url: &str) -> Result<Response> {
+        if self.offline {
+            info!("Offline mode, skipping request to {}", url);
+            self.skipped.borrow_mut().push(url.to_string());
+            return Err(HttpError::Offline(url.to_string()).into());
+        }
+        let parsed = Url::parse(url)?;
+        if self.respect_robots_txt && !self.is_allowed_by_robots(&parsed) {
+            return Err(HttpError::DisallowedByRobots(url.to_string()).into());
+        }
+        return self.request_with_retries(method, &parsed);
+    }
+
+    /// Whether or not network access is disabled.
+    pub fn is_offline(&self) -> bool {
+        return self.offline;
+    }
+
+    /// List of urls for which requests were skipped in offline mode.
+    pub fn skipped_requests(&self) -> Vec<String> {
+        return self.skipped.borrow().clone();
+    }
+
+    /// Fetch file from url saving it under path specified
+    pub fn download_file(&self, url: &str, f_name: &Path) -> Result<()> {
+        let mut response = self.get(url)?;
+        let mut file = File::create(f_name)?;
+        ioCopy(&mut response, &mut file)?;
+        Ok(())
+    }
+
+    fn request_with_retries(
+        &self,
+        method: Method,
+        url: &Url,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_domain(url);
+            let result =
+                self.client.request(method.clone(), url.as_str()).send();
+            let retryable = match &result {
+                Ok(r) => {
+                    r.status().is_server_error()
+                        || r.status() == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            if !retryable || attempt >= self.max_retries {
+                let response = result?;
+                if !response.status().is_success() {
+                    return Err(HttpError::RequestFailed(
+                        url.to_string(),
+                        response.status().as_u16(),
+                    )
+                    .into());
+                }
+                return Ok(response);
+            }
+            let backoff = self.retry_backoff * 2_u32.pow(attempt as u32);
+            info!("Retrying request to {} in {:?}", url, backoff);
+            sleep(backoff);
+            attempt += 1;
+        }
+    }
+
+    /// Wait until minimal delay between requests to the same
+    /// host has passed.
+    fn wait_for_domain(&self, url: &Url) {
+        let host = url.host_str().unwrap_or("").to_string();
+        let mut last_request = self.last_request.borrow_mut();
+        if let Some(last) = last_request.get(&host) {
+            let elapsed = last.elapsed();
+            if elapsed < self.domain_delay {
+                sleep(self.domain_delay - elapsed);
+            }
+        }
+        last_request.insert(host, Instant::now());
+    }
+
+    /// Check if url can be fetched according to the robots.txt
+    /// file for the host, missing robots files allow everything.
+    fn is_allowed_by_robots(&self, url: &Url) -> bool {
+        let host = url.host_str().unwrap_or("").to_string();
+        if !self.robots.borrow().contains_key(&host) {
+            let rules = match url.join("/robots.txt") {
+                Ok(robots_url) => match self
+                    .request_with_retries(Method::GET, &robots_url)
+                {
+                    Ok(r) => RobotsRules::parse(&r.text().unwrap_or_default()),
+                    Err(e) => {
+                        info!("Could not retrieve robots.txt: {}", e);
+                        RobotsRules::default()
+                    }
+                },
+                Err(_) => RobotsRules::default(),
+            };
+            self.robots.borrow_mut().insert(host.clone(), rules);
+        }
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        return self.robots.borrow()[&host].is_allowed(&path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parsing_robots_wildcard_rules() {
+        let rules = RobotsRules::parse(
+            "
+# comment
+User-agent: *
+Disallow: /private
+Allow: /private/public
+
+User-agent: otherbot
+Disallow: /
+",
+        );
+        assert!(rules.is_allowed("/"));
+        assert!(rules.is_allowed("/articles/1"));
+        assert!(!rules.is_allowed("/private/1"));
+        assert!(rules.is_allowed("/private/public/1"));
+    }
+
+    #[test]
+    fn test_parsing_robots_agent_specific_rules() {
+        let rules = RobotsRules::parse(
+            "
+User-agent: *
+Disallow: /
+
+User-agent: googlebot
+User-agent: Liveboat
+Disallow: /drafts
+",
+        );
+        assert!(rules.is_allowed("/articles/1"));
+        assert!(!rules.is_allowed("/drafts/1"));
+    }
+
+    #[test]
+    fn test_parsing_empty_robots() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow:\n");
+        assert!(rules.is_allowed("/anything"));
+        assert!(RobotsRules::parse("").is_allowed("/anything"));
+    }
+
+    #[test]
+    fn test_matching_robots_patterns() {
+        let rules = RobotsRules::parse(
+            "
+User-agent: *
+Disallow: /*.php$
+Disallow: /*?
+Disallow: /tmp/*/drafts
+Allow: /tmp/*/drafts/public
+",
+        );
+        assert!(rules.is_allowed("/"));
+        assert!(rules.is_allowed("/articles/1"));
+        assert!(rules.is_allowed("/index.php5"));
+        assert!(!rules.is_allowed("/index.php"));
+        assert!(!rules.is_allowed("/articles/1?page=2"));
+        assert!(!rules.is_allowed("/tmp/a/b/drafts/1"));
+        assert!(rules.is_allowed("/tmp/a/drafts/public/1"));
+        assert!(rules.is_allowed("/tmp/drafts"));
+        assert!(matches_robots_rule("/fish*", "/fish.html"));
+        assert!(matches_robots_rule("/$", "/"));
+        assert!(!matches_robots_rule("/fish$", "/fish.html"));
+    }
+}
@@ -1,12 +1,24 @@
+pub mod api;
 pub mod args;
+pub mod cache;
 pub mod cli;
 pub mod content;
 pub mod db;
+pub mod dedup;
 pub mod errors;
 pub mod feed;
 pub mod feed_item;
+pub mod highlight;
+pub mod http;
+pub mod links;
+pub mod media;
 pub mod opts;
 pub mod paths;
+pub mod plugins;
+pub mod podcast;
+pub mod sanitize;
+pub mod scripting;
+pub mod tagging;
 pub mod template;
 pub mod urls;
 pub mod utils;
@@ -0,0 +1,248 @@
+/// This module contains logic related to normalising article links,
+/// stripping tracking parameters and unwrapping redirectors.
+use log::info;
+
+use url::{form_urlencoded, Url};
+
+use crate::http::HttpClient;
+use crate::opts::Options;
+
+/// Maximum number of nested redirectors to unwrap.
+const MAX_UNWRAP_DEPTH: usize = 5;
+
+/// List of known redirector services, defined as (host, path, params)
+/// where params contain names of query parameters storing target url.
+/// Empty path matches any path for given host.
+const REDIRECTOR_PATTERNS: &[(&str, &str, &[&str])] = &[
+    ("google.com", "/url", &["q", "url"]),
+    ("l.facebook.com", "/l.php", &["u"]),
+    ("out.reddit.com", "", &["url"]),
+    ("feedproxy.google.com", "", &["url"]),
+    ("feeds.feedburner.com", "", &["url"]),
+];
+
+/// Host of FeedBurner proxy, `/~r/<feed>/~3/<id>/<target>` links
+/// embedding target url in the path are unwrapped locally while
+/// the ones without target can only be unwrapped by resolving
+/// HTTP redirects (see `link_resolve_redirects` option).
+const FEEDPROXY_HOST: &str = "feedproxy.google.com";
+
+/// Normaliser for article links, removing tracking parameters
+/// and unwrapping redirects so that the same article is
+/// always represented by the same url.
+pub struct LinkNormalizer<'a> {
+    client: &'a HttpClient,
+    tracking_params: &'a Vec<String>,
+    resolve_redirects: bool,
+}
+
+impl<'a> LinkNormalizer<'a> {
+    /// Initialize normaliser using settings defined in options.
+    pub fn init(
+        client: &'a HttpClient,
+        opts: &'a Options,
+    ) -> LinkNormalizer<'a> {
+        return LinkNormalizer {
+            client,
+            tracking_params: &opts.link_tracking_params,
+            resolve_redirects: opts.link_resolve_redirects,
+        };
+    }
+
+    /// Normalise url, resolving HTTP redirects via HEAD request
+    /// if enabled.
+    pub fn normalize(&self, url: &str) -> String {
+        let normalized = self.normalize_local(url);
+        if !self.resolve_redirects {
+            return normalized;
+        }
+        match self.client.head(&normalized) {
+            Ok(response) => {
+                let resolved = response.url().to_string();
+                if resolved == normalized {
+                    return normalized;
+                }
+                info!("Resolved redirect {} to {}", normalized, resolved);
+                return self.normalize_local(&resolved);
+            }
+            Err(e) => {
+                info!("Could not resolve redirects for {}: {}", normalized, e);
+                return normalized;
+            }
+        }
+    }
+
+    /// Normalise url without issuing any network requests,
+    /// unparseable urls are returned as is.
+    pub fn normalize_local(&self, url: &str) -> String {
+        let Ok(mut parsed) = Url::parse(url) else {
+            return url.to_string();
+        };
+        for _ in 0..MAX_UNWRAP_DEPTH {
+            match unwrap_redirector(&parsed) {
+                Some(target) => parsed = target,
+                None => break,
+            }
+        }
+        strip_tracking_params(&mut parsed, self.tracking_params);
+        return parsed.to_string();
+    }
+}
+
+/// Retrieve target url of known redirector service.
+fn unwrap_redirector(url: &Url) -> Option<Url> {
+    let host = url.host_str()?;
+    let target = get_param_target(url, host)
+        .or_else(|| get_embedded_target(url, host))?;
+    if target.scheme() != "http" && target.scheme() != "https" {
+        return None;
+    }
+    return Some(target);
+}
+
+/// Retrieve target url stored in query parameter of the redirector.
+fn get_param_target(url: &Url, host: &str) -> Option<Url> {
+    let (_, _, params) = REDIRECTOR_PATTERNS.iter().find(|(h, path, _)| {
+        let host_matches = host == *h || host.ends_with(&format!(".{}", h));
+        return host_matches && (path.is_empty() || url.path() == *path);
+    })?;
+    return url
+        .query_pairs()
+        .find(|(k, _)| params.contains(&k.as_ref()))
+        .and_then(|(_, v)| Url::parse(&v).ok());
+}
+
+/// Retrieve target url embedded in the path of FeedBurner proxy link.
+fn get_embedded_target(url: &Url, host: &str) -> Option<Url> {
+    // Matching on:
+    // /~r/<feed>/~3/<id>/<target>
+    if host != FEEDPROXY_HOST {
+        return None;
+    }
+    let parts: Vec<&str> = url.path().splitn(6, '/').collect();
+    if parts.len() != 6 || parts[1] != "~r" {
+        return None;
+    }
+    // Escape characters with special meaning in form encoding
+    // so that the whole target is decoded as a single value.
+    let encoded = format!(
+        "u={}",
+        parts[5]
+            .replace('+', "%2B")
+            .replace('&', "%26")
+            .replace('=', "%3D")
+    );
+    let (_, target) = form_urlencoded::parse(encoded.as_bytes()).next()?;
+    return Url::parse(&target).ok();
+}
+
+/// Remove query parameters matching tracking parameter list,
+/// entries ending with `*` are treated as prefixes.
+/// Remaining parameters are retained in their original form.
+fn strip_tracking_params(url: &mut Url, tracking_params: &[String]) {
+    let Some(query) = url.query() else {
+        return;
+    };
+    let is_tracking = |key: &str| {
+        let key = key.to_lowercase();
+        return tracking_params.iter().any(|p| {
+            let p = p.to_lowercase();
+            match p.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == p,
+            }
+        });
+    };
+    let retained: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or("");
+            return !pair.is_empty() && !is_tracking(key);
+        })
+        .collect();
+    if retained.is_empty() {
+        url.set_query(None);
+    } else {
+        url.set_query(Some(&retained.join("&")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn normalize(url: &str) -> String {
+        let opts = Options::default();
+        let client = HttpClient::init(&opts, true).unwrap();
+        let normalizer = LinkNormalizer::init(&client, &opts);
+        return normalizer.normalize_local(url);
+    }
+
+    #[test]
+    fn test_stripping_tracking_params() {
+        assert_eq!(
+            "https://test.com/article?id=1&q=a%20b#section",
+            normalize("https://test.com/article?utm_source=rss&id=1&UTM_Medium=x&fbclid=abc&q=a%20b#section")
+        );
+        assert_eq!(
+            "https://test.com/article",
+            normalize(
+                "https://test.com/article?utm_source=rss&utm_campaign=feed"
+            )
+        );
+        assert_eq!("not a url", normalize("not a url"));
+    }
+
+    #[test]
+    fn test_unwrapping_redirectors() {
+        assert_eq!(
+            "https://test.com/article?id=1",
+            normalize("https://www.google.com/url?rct=j&sa=t&url=https%3A%2F%2Ftest.com%2Farticle%3Fid%3D1%26utm_source%3Dalerts&ct=ga")
+        );
+        assert_eq!(
+            "https://test.com/article",
+            normalize("https://www.google.com/url?q=https%3A%2F%2Fl.facebook.com%2Fl.php%3Fu%3Dhttps%253A%252F%252Ftest.com%252Farticle")
+        );
+        assert_eq!(
+            "https://test.com/article?id=1",
+            normalize("http://feedproxy.google.com/~r/Test/~3/AbC123/https%3A%2F%2Ftest.com%2Farticle%3Fid%3D1?utm_source=feedburner")
+        );
+        assert_eq!(
+            "https://test.com/a+b",
+            normalize("http://feedproxy.google.com/~r/Test/~3/AbC123/https://test.com/a+b")
+        );
+        assert_eq!(
+            "https://test.com/article",
+            normalize("http://feedproxy.google.com/~r/Test/~3/AbC123/?url=https%3A%2F%2Ftest.com%2Farticle")
+        );
+        assert_eq!(
+            "https://test.com/article",
+            normalize("https://feeds.feedburner.com/Test?url=https%3A%2F%2Ftest.com%2Farticle")
+        );
+        // Links without embedded target require resolving redirects.
+        assert_eq!(
+            "http://feedproxy.google.com/~r/Test/~3/AbC123/article.html",
+            normalize("http://feedproxy.google.com/~r/Test/~3/AbC123/article.html?utm_source=feedburner")
+        );
+        assert_eq!(
+            "https://www.google.com/url?q=javascript:alert(1)",
+            normalize("https://www.google.com/url?q=javascript:alert(1)")
+        );
+        assert_eq!(
+            "https://www.google.com/search?q=https://test.com",
+            normalize("https://www.google.com/search?q=https://test.com")
+        );
+    }
+
+    #[test]
+    fn test_resolving_redirects() {
+        let mut opts = Options::default();
+        opts.link_resolve_redirects = true;
+        let client = HttpClient::init(&opts, true).unwrap();
+        let normalizer = LinkNormalizer::init(&client, &opts);
+        let url = "http://feedproxy.google.com/~r/Test/~3/AbC123/article.html";
+        assert_eq!(url, normalizer.normalize(url));
+        assert_eq!(vec![url.to_string()], client.skipped_requests());
+    }
+}
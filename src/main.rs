@@ -1,14 +1,26 @@
+mod api;
 mod args;
 mod builders;
+mod cache;
 mod cli;
 mod content;
 mod db;
+mod dedup;
 mod errors;
 mod feed;
 mod feed_item;
 mod handlers;
+mod highlight;
+mod http;
+mod links;
+mod media;
 mod opts;
 mod paths;
+mod plugins;
+mod podcast;
+mod sanitize;
+mod scripting;
+mod tagging;
 mod template;
 mod urls;
 mod utils;
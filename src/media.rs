@@ -0,0 +1,317 @@
+/// This module contains logic related to mirroring images referenced
+/// in article content so that these are served from the build directory.
+use log::info;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::http::HttpClient;
+use crate::opts::Options;
+
+/// Path of the media directory relative to the build directory.
+pub const MEDIA_DIRNAME: &str = "assets/media";
+/// Filename of the index mapping source urls to mirrored files.
+const MEDIA_INDEX_FILENAME: &str = "index.json";
+/// Content types of the mirrored images along with file extensions.
+const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/avif", "avif"),
+];
+/// Content types of the images which are never mirrored, SVG images
+/// can contain scripts which would run within the site origin.
+const SKIPPED_IMAGE_TYPES: [&str; 1] = ["image/svg+xml"];
+
+/// Mirror downloading images into build directory, deduplicating
+/// them by content hash.
+pub struct MediaMirror<'a> {
+    client: &'a HttpClient,
+    media_dir: PathBuf,
+    /// Url prefix used for rewritten image links.
+    url_prefix: String,
+    max_size: u64,
+    max_total_size: u64,
+    /// Total size of images downloaded during current build.
+    downloaded: Cell<u64>,
+    /// Map of source image urls to mirrored filenames.
+    index: RefCell<HashMap<String, String>>,
+    /// Filenames referenced by the content during current build.
+    used: RefCell<HashSet<String>>,
+}
+
+impl<'a> MediaMirror<'a> {
+    /// Initialize mirror, loading index of previously mirrored images.
+    pub fn init(
+        client: &'a HttpClient,
+        build_dir: &Path,
+        opts: &Options,
+    ) -> Result<MediaMirror<'a>> {
+        let media_dir = build_dir.join(MEDIA_DIRNAME);
+        fs::create_dir_all(&media_dir)?;
+        let index_path = media_dir.join(MEDIA_INDEX_FILENAME);
+        let mut index = HashMap::new();
+        if index_path.is_file() {
+            index = serde_json::from_str(&fs::read_to_string(index_path)?)
+                .unwrap_or_default();
+        }
+        return Ok(MediaMirror {
+            client,
+            media_dir,
+            url_prefix: format!(
+                "{}/{}",
+                opts.site_path.trim_end_matches('/'),
+                MEDIA_DIRNAME
+            ),
+            max_size: opts.mirror_images_max_size_kb * 1024,
+            max_total_size: opts.mirror_images_max_total_mb * 1024 * 1024,
+            downloaded: Cell::new(0),
+            index: RefCell::new(index),
+            used: RefCell::new(HashSet::new()),
+        });
+    }
+
+    /// Download images referenced in the content, returning content
+    /// with image urls pointing to the local copies.
+    pub fn mirror_content(&self, content: &str, base_url: &Url) -> String {
+        return rewrite_image_urls(content, |src| {
+            self.mirror_url(src, base_url)
+        });
+    }
+
+    /// Download single image, returning url of the local copy.
+    pub fn mirror_url(&self, src: &str, base_url: &Url) -> Option<String> {
+        let url = base_url.join(src).ok()?;
+        let fname = self.mirror_image(&url)?;
+        self.used.borrow_mut().insert(fname.clone());
+        return Some(format!("{}/{}", self.url_prefix, fname));
+    }
+
+    /// Retrieve local filename for the image, downloading it if
+    /// it has not been mirrored before.
+    fn mirror_image(&self, url: &Url) -> Option<String> {
+        if let Some(fname) = self.index.borrow().get(url.as_str()) {
+            if self.media_dir.join(fname).is_file() {
+                return Some(fname.clone());
+            }
+        }
+        if self.downloaded.get() >= self.max_total_size {
+            info!("Media size limit reached, skipping {}", url);
+            return None;
+        }
+        let data = match self.download_image(url) {
+            Ok(d) => d?,
+            Err(e) => {
+                info!("Could not mirror image {}: {}", url, e);
+                return None;
+            }
+        };
+        let fname = format!(
+            "{}.{}",
+            &format!("{:x}", Sha256::digest(&data.0))[..32],
+            data.1
+        );
+        let path = self.media_dir.join(&fname);
+        if !path.is_file() {
+            if let Err(e) = fs::write(&path, &data.0) {
+                info!("Could not save image {}: {}", path.display(), e);
+                return None;
+            }
+        }
+        self.downloaded
+            .set(self.downloaded.get() + data.0.len() as u64);
+        self.index
+            .borrow_mut()
+            .insert(url.to_string(), fname.clone());
+        return Some(fname);
+    }
+
+    /// Download image data along with file extension, images exceeding
+    /// size limit or with non image content type are skipped.
+    fn download_image(&self, url: &Url) -> Result<Option<(Vec<u8>, String)>> {
+        let response = self.client.get(url.as_str())?;
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        if !content_type.starts_with("image/") {
+            info!("Skipping non image content {}: {}", url, content_type);
+            return Ok(None);
+        }
+        let Some(ext) = image_extension(&content_type, url) else {
+            info!("Skipping unsupported image {}: {}", url, content_type);
+            return Ok(None);
+        };
+        if response.content_length().unwrap_or(0) > self.max_size {
+            info!("Image {} exceeds size limit", url);
+            return Ok(None);
+        }
+        let mut data = Vec::new();
+        response.take(self.max_size + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > self.max_size {
+            info!("Image {} exceeds size limit", url);
+            return Ok(None);
+        }
+        return Ok(Some((data, ext)));
+    }
+
+    /// Save index of mirrored images and remove media files which
+    /// are no longer referenced by any article.
+    pub fn finalize(&self) -> Result<()> {
+        let used = self.used.borrow();
+        self.index.borrow_mut().retain(|_, f| used.contains(f));
+        for entry in fs::read_dir(&self.media_dir)? {
+            let entry = entry?;
+            let fname = entry.file_name().to_string_lossy().to_string();
+            if fname == MEDIA_INDEX_FILENAME || used.contains(&fname) {
+                continue;
+            }
+            info!("Removing unused media file {}", fname);
+            _ = fs::remove_file(entry.path());
+        }
+        fs::write(
+            self.media_dir.join(MEDIA_INDEX_FILENAME),
+            serde_json::to_string(&*self.index.borrow())?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Retrieve file extension for image based on content type, falling
+/// back to extension defined in url if it matches one of the mirrored
+/// image types. Returns None for images which should not be mirrored.
+fn image_extension(content_type: &str, url: &Url) -> Option<String> {
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    if SKIPPED_IMAGE_TYPES.contains(&mime) {
+        return None;
+    }
+    if let Some((_, ext)) = IMAGE_TYPES.iter().find(|(m, _)| *m == mime) {
+        return Some(ext.to_string());
+    }
+    let path_ext = Path::new(url.path())
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+    return match path_ext {
+        Some(e) if e == "jpeg" || IMAGE_TYPES.iter().any(|(_, x)| *x == e) => {
+            Some(e)
+        }
+        _ => Some(String::from("img")),
+    };
+}
+
+/// Rewrite `src` and `srcset` attributes of all image tags in the
+/// content, urls for which rewrite function returns None are left intact.
+pub fn rewrite_image_urls<F>(content: &str, rewrite: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let img_re = Regex::new(r#"(?i)<img\b[^>]*>"#).unwrap();
+    let attr_re = Regex::new(
+        r#"(?i)(?<name>\ssrcset|\ssrc)\s*=\s*(?<q>["'])(?<val>[^"']*)["']"#,
+    )
+    .unwrap();
+    return img_re
+        .replace_all(content, |img: &Captures| {
+            attr_re
+                .replace_all(&img[0], |attr: &Captures| {
+                    let val = &attr["val"];
+                    let new_val = if attr["name"].to_lowercase() == " srcset" {
+                        rewrite_srcset(val, &rewrite)
+                    } else {
+                        rewrite(val).unwrap_or(val.to_string())
+                    };
+                    format!(
+                        "{}={}{}{}",
+                        &attr["name"], &attr["q"], new_val, &attr["q"]
+                    )
+                })
+                .to_string()
+        })
+        .to_string();
+}
+
+/// Rewrite each candidate url in srcset attribute.
+fn rewrite_srcset<F>(srcset: &str, rewrite: &F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    return srcset
+        .split(',')
+        .map(|candidate| {
+            let candidate = candidate.trim();
+            let mut parts = candidate.splitn(2, char::is_whitespace);
+            let url = parts.next().unwrap_or("");
+            let descriptor = parts.next();
+            let new_url = rewrite(url).unwrap_or(url.to_string());
+            match descriptor {
+                Some(d) => format!("{} {}", new_url, d.trim()),
+                None => new_url,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rewriting_image_urls() {
+        let content = r#"<p>Text <img src="http://test.com/a.png" alt="a"><img class="x" SRC='http://test.com/b.png'></p><a href="http://test.com/a.png">link</a>"#;
+        let result = rewrite_image_urls(content, |u| {
+            Some(u.replace("http://test.com/", "/assets/media/"))
+        });
+        assert_eq!(
+            r#"<p>Text <img src="/assets/media/a.png" alt="a"><img class="x" SRC='/assets/media/b.png'></p><a href="http://test.com/a.png">link</a>"#,
+            result
+        );
+    }
+
+    #[test]
+    fn test_rewriting_srcset_urls() {
+        let content = r#"<img src="http://test.com/a.png" srcset="http://test.com/a.png 1x, http://test.com/a2.png 2x">"#;
+        let result = rewrite_image_urls(content, |u| {
+            if u.ends_with("a2.png") {
+                return None;
+            }
+            Some(String::from("/local.png"))
+        });
+        assert_eq!(
+            r#"<img src="/local.png" srcset="/local.png 1x, http://test.com/a2.png 2x">"#,
+            result
+        );
+    }
+
+    #[test]
+    fn test_retrieving_image_extension() {
+        let url = Url::parse("http://test.com/image.JPEG?size=large").unwrap();
+        assert_eq!(Some("png".to_string()), image_extension("image/png", &url));
+        assert_eq!(None, image_extension("image/svg+xml; charset=utf-8", &url));
+        assert_eq!(
+            Some("jpeg".to_string()),
+            image_extension("image/x-unknown", &url)
+        );
+        let url = Url::parse("http://test.com/image").unwrap();
+        assert_eq!(
+            Some("img".to_string()),
+            image_extension("image/x-unknown", &url)
+        );
+        let url = Url::parse("http://test.com/image.svg").unwrap();
+        assert_eq!(
+            Some("img".to_string()),
+            image_extension("image/x-unknown", &url)
+        );
+    }
+}
@@ -8,6 +8,8 @@ use std::str;
 use anyhow::Result;
 use toml;
 
+use crate::http::default_user_agent;
+
 const fn default_bool<const V: bool>() -> bool {
     V
 }
@@ -43,6 +45,126 @@ fn default_template_name() -> String {
     String::from("default")
 }
 
+fn default_http_user_agent() -> String {
+    default_user_agent()
+}
+
+fn default_opml_channel_format() -> String {
+    String::from("rss")
+}
+
+fn default_archive_pagination() -> String {
+    String::from("size")
+}
+
+fn default_empty() -> String {
+    String::new()
+}
+
+fn default_empty_list() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_sanitize_iframe_hosts() -> Vec<String> {
+    vec![String::from("www.youtube-nocookie.com")]
+}
+
+fn default_content_rejection_markers() -> Vec<String> {
+    [
+        "enable javascript",
+        "accept cookies",
+        "cookie consent",
+        "cookie settings",
+        "subscribe to continue",
+        "subscribe to read",
+        "subscribers only",
+        "sign in to continue",
+        "log in to continue",
+        "are you a robot",
+        "verify you are human",
+        "access denied",
+    ]
+    .iter()
+    .map(|m| m.to_string())
+    .collect()
+}
+
+fn default_link_tracking_params() -> Vec<String> {
+    [
+        "utm_*", "fbclid", "gclid", "dclid", "yclid", "msclkid", "mc_cid",
+        "mc_eid", "_hsenc", "_hsmi", "mkt_tok", "igshid", "ref_src",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
+/// Definition of external content plugin, plugin is run for
+/// articles matching any of the domains or feed url patterns.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PluginConfig {
+    /// Shell command used for running the plugin.
+    pub command: String,
+    /// List of article domains (including subdomains) plugin applies to.
+    #[serde(default = "default_empty_list")]
+    pub domains: Vec<String>,
+    /// List of regex patterns matched against the feed url.
+    #[serde(default = "default_empty_list")]
+    pub feed_patterns: Vec<String>,
+    /// Maximum time (in seconds) plugin is allowed to run for single article.
+    #[serde(default = "default_u64::<10>")]
+    pub timeout: u64,
+}
+
+fn default_plugins() -> Vec<PluginConfig> {
+    Vec::new()
+}
+
+/// Definition of article tagging rule, tags are assigned to
+/// articles matching all the conditions defined by the rule.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TagRule {
+    /// List of tags assigned to matching articles.
+    pub tags: Vec<String>,
+    /// Newsboat filter expression, eg. `title =~ "rust"`.
+    pub filter: Option<String>,
+    /// Regex pattern matched against article title.
+    pub title_pattern: Option<String>,
+    /// Regex pattern matched against article content.
+    pub content_pattern: Option<String>,
+}
+
+fn default_tag_rules() -> Vec<TagRule> {
+    Vec::new()
+}
+
+/// Definition of article highlight rule, equivalent of Newsboat
+/// `highlight-article` directive.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HighlightRule {
+    /// Newsboat filter expression matched against the article.
+    pub filter: String,
+    /// Optional name of the highlight, used as style name by templates.
+    pub name: Option<String>,
+    /// Foreground color of the highlighted article.
+    #[serde(default = "default_highlight_color")]
+    pub foreground: String,
+    /// Background color of the highlighted article.
+    #[serde(default = "default_highlight_color")]
+    pub background: String,
+    /// Additional text attributes, eg. `bold`, `underline`.
+    #[serde(default = "default_empty_list")]
+    pub attributes: Vec<String>,
+}
+
+fn default_highlight_color() -> String {
+    String::from("default")
+}
+
+fn default_highlight_rules() -> Vec<HighlightRule> {
+    Vec::new()
+}
+
 /// This represents main configuration options
 /// available to the user.
 #[derive(Deserialize, Serialize, Debug)]
@@ -68,10 +190,91 @@ pub struct Options {
     /// retrieved data.
     #[serde(default = "default_bool::<true>")]
     pub scrape_hn_links: bool,
+    /// If set to true Liveboat will retrieve comment links and scrape
+    /// external articles for other discussion aggregators (lobste.rs,
+    /// tildes.net, Lemmy instances and Slashdot).
+    #[serde(default = "default_bool::<true>")]
+    pub scrape_aggregator_links: bool,
     /// Define whether or not to include article content in generated
     /// rss feeds (might increase size significantly)
     #[serde(default = "default_bool::<true>")]
     pub include_article_content_in_rss_feeds: bool,
+    /// Number of minutes RSS channels can be cached for before
+    /// refreshing (`ttl` element), 0 to omit.
+    #[serde(default = "default_u64::<60>")]
+    pub rss_channel_ttl: u64,
+    /// Whether or not to add iTunes/Podcasting 2.0 elements to
+    /// channels containing audio or video enclosures and resolve
+    /// enclosure sizes.
+    #[serde(default = "default_bool::<false>")]
+    pub podcast_channels: bool,
+    /// Value of the explicit flag set for podcast channels.
+    #[serde(default = "default_bool::<false>")]
+    pub podcast_explicit: bool,
+    /// Url of the image used as podcast channel cover.
+    #[serde(default = "default_empty")]
+    pub podcast_image: String,
+    /// Whether or not to generate Atom feeds alongside RSS channels.
+    #[serde(default = "default_bool::<true>")]
+    pub atom_feeds: bool,
+    /// Whether or not to generate JSON Feed 1.1 documents.
+    #[serde(default = "default_bool::<true>")]
+    pub json_feeds: bool,
+    /// Whether or not to re-publish channels for every url feed,
+    /// containing articles as processed by Liveboat.
+    #[serde(default = "default_bool::<false>")]
+    pub url_feed_channels: bool,
+    /// Whether or not to publish channels aggregating articles
+    /// for every feed and article tag.
+    #[serde(default = "default_bool::<false>")]
+    pub tag_channels: bool,
+    /// If set to true url feeds in OPML file will point to
+    /// channels re-published by Liveboat instead of upstream urls.
+    #[serde(default = "default_bool::<false>")]
+    pub opml_liveboat_channels: bool,
+    /// Whether or not to generate static full text search index.
+    #[serde(default = "default_bool::<false>")]
+    pub search_index: bool,
+    /// Maximum number of words of the article text to be
+    /// included in the search index.
+    #[serde(default = "default_u64::<300>")]
+    pub search_index_max_words: u64,
+    /// Method of splitting feed archives into pages,
+    /// either `size` or `month`.
+    #[serde(default = "default_archive_pagination")]
+    pub archive_pagination: String,
+    /// Number of articles per archive page when paginating by size.
+    #[serde(default = "default_u64::<200>")]
+    pub archive_page_size: u64,
+    /// Whether or not to save single file feed archives
+    /// (`feeds/<id>_archive.json`) alongside archive pages.
+    #[serde(default = "default_bool::<true>")]
+    pub legacy_archive_files: bool,
+    /// Whether or not to write gzip and brotli compressed variants
+    /// of the generated html, json and xml files.
+    #[serde(default = "default_bool::<false>")]
+    pub precompress_outputs: bool,
+    /// Whether or not to create content hashed copies of template
+    /// assets and feed data, referenced from the rendered page.
+    #[serde(default = "default_bool::<false>")]
+    pub fingerprint_assets: bool,
+    /// Whether or not to generate sitemap of the site pages.
+    #[serde(default = "default_bool::<true>")]
+    pub sitemap: bool,
+    /// Whether or not to generate `robots.txt` file.
+    #[serde(default = "default_bool::<true>")]
+    pub robots_txt: bool,
+    /// List of paths disallowed in `robots.txt`.
+    #[serde(default = "default_empty_list")]
+    pub robots_disallow: Vec<String>,
+    /// If set to true the site is excluded from indexing by
+    /// search engines.
+    #[serde(default = "default_bool::<false>")]
+    pub private_site: bool,
+    /// Format of liveboat channels referenced in OPML file,
+    /// either `rss`, `atom` or `json`.
+    #[serde(default = "default_opml_channel_format")]
+    pub opml_channel_format: String,
     /// Path to Newsboat urls file
     #[serde(default = "default_newsboat_url_file")]
     pub newsboat_urls_file: String,
@@ -87,6 +290,122 @@ pub struct Options {
     /// Name of the template to use
     #[serde(default = "default_template_name")]
     pub template_name: String,
+    /// Timeout (in seconds) for establishing connection
+    /// when scraping or downloading files.
+    #[serde(default = "default_u64::<10>")]
+    pub http_connect_timeout: u64,
+    /// Timeout (in seconds) for retrieving the response.
+    #[serde(default = "default_u64::<30>")]
+    pub http_read_timeout: u64,
+    /// User agent sent with every request.
+    #[serde(default = "default_http_user_agent")]
+    pub http_user_agent: String,
+    /// Optional proxy url used for all requests, eg. socks5://localhost:1080
+    #[serde(default = "default_empty")]
+    pub http_proxy: String,
+    /// Optional path to PEM file containing additional root certificates.
+    #[serde(default = "default_empty")]
+    pub http_ca_bundle: String,
+    /// Number of retries for failed requests (timeouts, server errors).
+    #[serde(default = "default_u64::<2>")]
+    pub http_max_retries: u64,
+    /// Base delay (in milliseconds) between retries, doubled
+    /// with every subsequent attempt.
+    #[serde(default = "default_u64::<500>")]
+    pub http_retry_backoff_ms: u64,
+    /// Minimal delay (in milliseconds) between requests to the same domain.
+    #[serde(default = "default_u64::<1000>")]
+    pub http_domain_delay_ms: u64,
+    /// Whether or not to skip scraping pages disallowed by robots.txt
+    #[serde(default = "default_bool::<false>")]
+    pub http_respect_robots_txt: bool,
+    /// If set to true Liveboat will download images referenced in
+    /// article content and serve them from the build directory.
+    #[serde(default = "default_bool::<false>")]
+    pub mirror_images: bool,
+    /// Maximum size (in kilobytes) of single mirrored image.
+    #[serde(default = "default_u64::<2048>")]
+    pub mirror_images_max_size_kb: u64,
+    /// Maximum size (in megabytes) of images downloaded during single build.
+    #[serde(default = "default_u64::<200>")]
+    pub mirror_images_max_total_mb: u64,
+    /// Whether or not to sanitize html content of the articles.
+    #[serde(default = "default_bool::<true>")]
+    pub sanitize_content: bool,
+    /// Tags allowed in the article content in addition to the
+    /// default allowlist.
+    #[serde(default = "default_empty_list")]
+    pub sanitize_extra_tags: Vec<String>,
+    /// Attributes allowed for all the tags in addition to the
+    /// default allowlist.
+    #[serde(default = "default_empty_list")]
+    pub sanitize_extra_attributes: Vec<String>,
+    /// List of hosts embedded iframes are allowed to point to.
+    #[serde(default = "default_sanitize_iframe_hosts")]
+    pub sanitize_iframe_hosts: Vec<String>,
+    /// Whether or not to remove 1x1 tracking images.
+    #[serde(default = "default_bool::<true>")]
+    pub sanitize_remove_tracking_pixels: bool,
+    /// Whether or not to normalise article links, stripping tracking
+    /// parameters and unwrapping known redirectors.
+    #[serde(default = "default_bool::<true>")]
+    pub link_normalization: bool,
+    /// List of query parameters removed from article links,
+    /// entries ending with `*` match parameter prefix.
+    #[serde(default = "default_link_tracking_params")]
+    pub link_tracking_params: Vec<String>,
+    /// If set to true Liveboat will follow HTTP redirects for
+    /// article links in order to retrieve canonical url.
+    #[serde(default = "default_bool::<false>")]
+    pub link_resolve_redirects: bool,
+    /// Whether or not to detect articles published by multiple feeds
+    /// and collapse these into single item.
+    #[serde(default = "default_bool::<true>")]
+    pub dedup_articles: bool,
+    /// Minimum title similarity (in percent) for articles to be
+    /// considered duplicates, 0 disables title matching.
+    #[serde(default = "default_u64::<0>")]
+    pub dedup_title_similarity: u64,
+    /// Reading speed (in words per minute) used when estimating
+    /// article reading time.
+    #[serde(default = "default_u64::<200>")]
+    pub reading_speed_wpm: u64,
+    /// Maximum length (in characters) of generated article
+    /// excerpts, 0 disables excerpt generation.
+    #[serde(default = "default_u64::<300>")]
+    pub excerpt_length: u64,
+    /// Define whether or not to include full article content in
+    /// truncated feed data, when disabled only the excerpts are
+    /// included (full content is still available in archive data).
+    #[serde(default = "default_bool::<true>")]
+    pub include_content_in_truncated_feeds: bool,
+    /// Minimum length of the article text for scraped or extracted
+    /// content to be used, shorter content falls back to the next
+    /// source (scraped, extracted and raw feed content).
+    #[serde(default = "default_u64::<250>")]
+    pub content_min_text_length: u64,
+    /// List of phrases marking consent, paywall or bot check pages,
+    /// content containing any of these falls back to the next source.
+    #[serde(default = "default_content_rejection_markers")]
+    pub content_rejection_markers: Vec<String>,
+    /// Whether or not to use `highlight-article` rules defined
+    /// in Newsboat config file.
+    #[serde(default = "default_bool::<true>")]
+    pub newsboat_highlights: bool,
+    /// List of Rhai scripts to run during the build, relative
+    /// paths are resolved against scripts dir in config directory.
+    #[serde(default = "default_empty_list")]
+    pub scripts: Vec<String>,
+    /// List of external content processing plugins.
+    #[serde(default = "default_plugins")]
+    pub plugins: Vec<PluginConfig>,
+    /// List of rules used for assigning tags to articles.
+    #[serde(default = "default_tag_rules")]
+    pub tag_rules: Vec<TagRule>,
+    /// List of article highlight rules, applied after those
+    /// defined in Newsboat config.
+    #[serde(default = "default_highlight_rules")]
+    pub highlight_rules: Vec<HighlightRule>,
 }
 
 impl Options {
@@ -107,12 +426,66 @@ impl Options {
             show_read_articles: true,
             template_name: default_template_name(),
             include_article_content_in_rss_feeds: true,
+            rss_channel_ttl: 60,
+            podcast_channels: false,
+            podcast_explicit: false,
+            podcast_image: default_empty(),
+            atom_feeds: true,
+            json_feeds: true,
+            url_feed_channels: false,
+            tag_channels: false,
+            opml_liveboat_channels: false,
+            search_index: false,
+            search_index_max_words: 300,
+            archive_pagination: default_archive_pagination(),
+            archive_page_size: 200,
+            legacy_archive_files: true,
+            precompress_outputs: false,
+            fingerprint_assets: false,
+            sitemap: true,
+            robots_txt: true,
+            robots_disallow: default_empty_list(),
+            private_site: false,
+            opml_channel_format: default_opml_channel_format(),
             time_threshold: 20,
             scrape_reddit_links: true,
             scrape_hn_links: true,
+            scrape_aggregator_links: true,
             newsboat_urls_file: default_newsboat_url_file(),
             newsboat_cache_file: default_newsboat_cache_file(),
             build_dir: default_build_dir(),
+            http_connect_timeout: 10,
+            http_read_timeout: 30,
+            http_user_agent: default_http_user_agent(),
+            http_proxy: default_empty(),
+            http_ca_bundle: default_empty(),
+            http_max_retries: 2,
+            http_retry_backoff_ms: 500,
+            http_domain_delay_ms: 1000,
+            http_respect_robots_txt: false,
+            mirror_images: false,
+            mirror_images_max_size_kb: 2048,
+            mirror_images_max_total_mb: 200,
+            sanitize_content: true,
+            sanitize_extra_tags: default_empty_list(),
+            sanitize_extra_attributes: default_empty_list(),
+            sanitize_iframe_hosts: default_sanitize_iframe_hosts(),
+            sanitize_remove_tracking_pixels: true,
+            link_normalization: true,
+            link_tracking_params: default_link_tracking_params(),
+            link_resolve_redirects: false,
+            dedup_articles: true,
+            dedup_title_similarity: 0,
+            reading_speed_wpm: 200,
+            excerpt_length: 300,
+            include_content_in_truncated_feeds: true,
+            content_min_text_length: 250,
+            content_rejection_markers: default_content_rejection_markers(),
+            newsboat_highlights: true,
+            scripts: default_empty_list(),
+            plugins: default_plugins(),
+            tag_rules: default_tag_rules(),
+            highlight_rules: default_highlight_rules(),
         };
     }
 
@@ -135,6 +508,12 @@ impl Options {
     pub fn template_name(&self) -> &String {
         return &self.template_name;
     }
+
+    /// Whether or not `site_url` has been changed from the placeholder.
+    pub fn has_site_url(&self) -> bool {
+        return !self.site_url.is_empty()
+            && self.site_url != default_site_url();
+    }
 }
 
 impl fmt::Display for Options {
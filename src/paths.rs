@@ -14,6 +14,9 @@ const LIVEBOAT_DEFAULT_CONFIG_FILENAME: &str = "config.toml";
 const LIVEBOAT_DEFAULT_BUILD_DIRNAME: &str = "liveboat_build";
 const LIVEBOAT_DEFAULT_CONFIG_DIRNAME: &str = ".config/liveboat";
 const LIVEBOAT_DEFAULT_TEMPLATES_DIRNAME: &str = "templates";
+const LIVEBOAT_CONTENT_CACHE_FILENAME: &str = "content_cache.json";
+const LIVEBOAT_ENCLOSURE_CACHE_FILENAME: &str = "enclosure_cache.json";
+const LIVEBOAT_DEFAULT_SCRIPTS_DIRNAME: &str = "scripts";
 
 /// This module stores all the paths used by the application.
 #[derive(Debug, Default)]
@@ -30,6 +33,8 @@ pub struct Paths {
     template_dir: PathBuf,
     /// Path to Newsboat urls file.
     url_file: PathBuf,
+    /// Path to Newsboat config file.
+    newsboat_config_file: PathBuf,
     /// Path to temporary file used for building the page.
     tmp_dir: PathBuf,
     /// Optional path to template to be used for generating the page
@@ -44,6 +49,7 @@ impl Paths {
         let mut paths = Paths {
             cache_file: PathBuf::new(),
             url_file: PathBuf::new(),
+            newsboat_config_file: PathBuf::new(),
             config_file: PathBuf::new(),
             build_dir: PathBuf::new(),
             tmp_dir: PathBuf::new(),
@@ -89,6 +95,7 @@ impl Paths {
         };
         paths.url_file = n_config.url_file().to_path_buf();
         paths.cache_file = n_config.cache_file().to_path_buf();
+        paths.newsboat_config_file = n_config.config_file().to_path_buf();
 
         return Ok(paths);
     }
@@ -164,6 +171,11 @@ impl Paths {
         return &self.url_file;
     }
 
+    /// Path to Newsboat config file.
+    pub fn newsboat_config_file(&self) -> &Path {
+        return &self.newsboat_config_file;
+    }
+
     /// Path to main Liveboat config.
     pub fn config_file(&self) -> &Path {
         return &self.config_file;
@@ -186,6 +198,21 @@ impl Paths {
         return &self.tmp_dir;
     }
 
+    /// Path to file storing cached article content.
+    pub fn content_cache_file(&self) -> PathBuf {
+        return self.config_dir.join(LIVEBOAT_CONTENT_CACHE_FILENAME);
+    }
+
+    /// Path to file storing cached enclosure sizes.
+    pub fn enclosure_cache_file(&self) -> PathBuf {
+        return self.config_dir.join(LIVEBOAT_ENCLOSURE_CACHE_FILENAME);
+    }
+
+    /// Path to directory storing user scripts.
+    pub fn scripts_dir(&self) -> PathBuf {
+        return self.config_dir.join(LIVEBOAT_DEFAULT_SCRIPTS_DIRNAME);
+    }
+
     /// Path to Liveboat template dir.
     pub fn template_dir(&self) -> &Path {
         return &self.template_dir;
@@ -220,7 +247,8 @@ impl fmt::Display for Paths {
             template_path: {}
             build_dir: {}
             cache_file: {}
-            url_file: {}",
+            url_file: {}
+            newsboat_config_file: {}",
             self.config_file.display(),
             self.config_dir.display(),
             self.tmp_dir.display(),
@@ -228,7 +256,8 @@ impl fmt::Display for Paths {
             self.template_path.display(),
             self.build_dir.display(),
             self.cache_file.display(),
-            self.url_file.display()
+            self.url_file.display(),
+            self.newsboat_config_file.display()
         )
     }
 }
@@ -0,0 +1,295 @@
+/// This module contains logic related to running external content
+/// processing plugins. Plugins receive article data as JSON on stdin
+/// and return modified fields as JSON on stdout.
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use wait_timeout::ChildExt;
+
+use crate::content::strip_html_tags;
+use crate::errors::PluginError;
+use crate::feed_item::FeedItem;
+use crate::opts::{Options, PluginConfig};
+
+/// Article data passed to the plugin.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginInput<'a> {
+    title: &'a String,
+    url: &'a String,
+    original_url: &'a String,
+    feed_url: &'a String,
+    author: &'a String,
+    date: i64,
+    content: &'a String,
+    text: &'a Option<String>,
+    comments_url: &'a Option<String>,
+    enclosure_url: &'a Option<String>,
+    enclosure_mime: &'a Option<String>,
+    extra: &'a BTreeMap<String, String>,
+}
+
+/// Fields returned by the plugin, missing fields are left intact.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+struct PluginOutput {
+    title: Option<String>,
+    content: Option<String>,
+    url: Option<String>,
+    comments_url: Option<String>,
+    extra: BTreeMap<String, String>,
+    drop: bool,
+}
+
+/// Runner executing plugins defined in options for matching articles.
+pub struct PluginRunner<'a> {
+    plugins: &'a Vec<PluginConfig>,
+    feed_patterns: Vec<Vec<Regex>>,
+}
+
+impl<'a> PluginRunner<'a> {
+    /// Initialize runner, validating plugin feed patterns.
+    pub fn init(opts: &'a Options) -> Result<PluginRunner<'a>> {
+        let mut feed_patterns = Vec::new();
+        for p in &opts.plugins {
+            let mut patterns = Vec::new();
+            for pattern in &p.feed_patterns {
+                patterns.push(Regex::new(pattern).map_err(|e| {
+                    PluginError::InvalidPattern(pattern.clone(), e.to_string())
+                })?);
+            }
+            feed_patterns.push(patterns);
+        }
+        return Ok(PluginRunner {
+            plugins: &opts.plugins,
+            feed_patterns,
+        });
+    }
+
+    /// Run all the plugins matching the article, returns false
+    /// if article should be dropped. Plugin failures are logged
+    /// and leave the article intact.
+    pub fn process(&self, item: &mut FeedItem) -> bool {
+        for (idx, plugin) in self.plugins.iter().enumerate() {
+            if !self.matches(idx, item) {
+                continue;
+            }
+            info!("Running plugin {} for {}", plugin.command, item.url());
+            match run_plugin(plugin, item) {
+                Ok(output) => {
+                    if output.drop {
+                        info!("Plugin {} dropped {}", plugin.command, item);
+                        return false;
+                    }
+                    apply_output(item, output);
+                }
+                Err(e) => warn!("Plugin error for {}: {}", item.url(), e),
+            }
+        }
+        return true;
+    }
+
+    /// Check if plugin applies to given article, either by article
+    /// domain or feed url pattern.
+    fn matches(&self, idx: usize, item: &FeedItem) -> bool {
+        let host = Url::parse(item.url())
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+            .unwrap_or_default();
+        let domain_match = self.plugins[idx]
+            .domains
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{}", d)));
+        return domain_match
+            || self.feed_patterns[idx]
+                .iter()
+                .any(|p| p.is_match(item.feed_url()));
+    }
+}
+
+/// Execute plugin command passing article data on stdin.
+fn run_plugin(plugin: &PluginConfig, item: &FeedItem) -> Result<PluginOutput> {
+    let input = serde_json::to_string(&PluginInput {
+        title: item.title(),
+        url: item.url(),
+        original_url: item.original_url(),
+        feed_url: item.feed_url(),
+        author: item.author(),
+        date: item.date(),
+        content: item.content(),
+        text: item.text(),
+        comments_url: item.comments_url(),
+        enclosure_url: item.enc_url(),
+        enclosure_mime: item.enc_mime(),
+        extra: item.extra(),
+    })?;
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&plugin.command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Pipes are handled in separate threads so that plugins
+    // producing large output do not block on full buffers.
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || {
+        _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        _ = stdout.read_to_string(&mut out);
+        out
+    });
+    let mut stderr = child.stderr.take().unwrap();
+    let err_reader = thread::spawn(move || {
+        let mut out = String::new();
+        _ = stderr.read_to_string(&mut out);
+        out
+    });
+    let status =
+        match child.wait_timeout(Duration::from_secs(plugin.timeout))? {
+            Some(s) => s,
+            None => {
+                _ = child.kill();
+                _ = child.wait();
+                return Err(PluginError::Timeout(
+                    plugin.command.clone(),
+                    plugin.timeout,
+                )
+                .into());
+            }
+        };
+    _ = writer.join();
+    let out = reader.join().unwrap_or_default();
+    let err = err_reader.join().unwrap_or_default();
+    if !err.trim().is_empty() {
+        info!("Plugin {} stderr: {}", plugin.command, err.trim());
+    }
+    if !status.success() {
+        return Err(PluginError::Failed(
+            plugin.command.clone(),
+            status.code().unwrap_or(-1),
+        )
+        .into());
+    }
+    if out.trim().is_empty() {
+        return Ok(PluginOutput::default());
+    }
+    return Ok(serde_json::from_str(&out).map_err(|e| {
+        PluginError::InvalidOutput(plugin.command.clone(), e.to_string())
+    })?);
+}
+
+/// Update article with fields returned by the plugin.
+fn apply_output(item: &mut FeedItem, output: PluginOutput) {
+    if let Some(title) = output.title {
+        item.set_title(title);
+    }
+    if let Some(content) = output.content {
+        let text = strip_html_tags(&content);
+        item.set_content_length(text.len());
+        item.set_text(text);
+        item.set_content(content);
+    }
+    if let Some(url) = output.url {
+        item.set_url(url);
+    }
+    if let Some(comments_url) = output.comments_url {
+        item.set_comments_url(comments_url);
+    }
+    for (key, value) in output.extra {
+        item.set_extra(key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use libnewsboat::matchable::Matchable;
+
+    fn init_item() -> FeedItem {
+        return FeedItem::new(
+            "Title",
+            "https://blog.example.com/post",
+            "https://example.com/rss",
+            "",
+            123456,
+            false,
+            "<p>Content</p>",
+            1,
+        );
+    }
+
+    fn init_opts(command: &str, timeout: u64) -> Options {
+        let mut opts = Options::default();
+        opts.plugins = vec![PluginConfig {
+            command: command.to_string(),
+            domains: vec!["example.com".to_string()],
+            feed_patterns: Vec::new(),
+            timeout,
+        }];
+        return opts;
+    }
+
+    #[test]
+    fn test_running_plugin() {
+        let opts = init_opts(
+            r#"sed -e 's/"title":"Title"/"title":"Changed"/' \
+                -e 's/"extra":{}/"extra":{"source":"plugin"}/'"#,
+            5,
+        );
+        let runner = PluginRunner::init(&opts).unwrap();
+        let mut item = init_item();
+        assert!(runner.process(&mut item));
+        assert_eq!("Changed", item.title());
+        assert_eq!("<p>Content</p>", item.content());
+        assert_eq!(Some("plugin".to_string()), item.attribute_value("source"));
+    }
+
+    #[test]
+    fn test_dropping_items_via_plugin() {
+        let opts = init_opts(r#"cat > /dev/null; echo '{"drop": true}'"#, 5);
+        let runner = PluginRunner::init(&opts).unwrap();
+        assert!(!runner.process(&mut init_item()));
+
+        let mut item = init_item();
+        item.set_url("https://other.com/post".to_string());
+        assert!(runner.process(&mut item));
+    }
+
+    #[test]
+    fn test_isolating_plugin_errors() {
+        let mut item = init_item();
+        for cmd in ["sleep 5", "exit 3", "echo invalid"] {
+            let opts = init_opts(cmd, 1);
+            let runner = PluginRunner::init(&opts).unwrap();
+            let plugin = &opts.plugins[0];
+            assert!(run_plugin(plugin, &item).is_err());
+            assert!(runner.process(&mut item));
+            assert_eq!("Title", item.title());
+        }
+    }
+
+    #[test]
+    fn test_matching_feed_patterns() {
+        let mut opts = init_opts("cat", 5);
+        opts.plugins[0].domains = Vec::new();
+        opts.plugins[0].feed_patterns = vec![r"^https://example\.com/".into()];
+        let runner = PluginRunner::init(&opts).unwrap();
+        assert!(runner.matches(0, &init_item()));
+
+        opts.plugins[0].feed_patterns = vec!["[invalid".into()];
+        assert!(PluginRunner::init(&opts).is_err());
+    }
+}
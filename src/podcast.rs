@@ -0,0 +1,251 @@
+/// This module contains logic related to generating podcast
+/// compatible channels for articles containing audio and video
+/// enclosures (iTunes and Podcasting 2.0 namespaces).
+use log::{info, warn};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{read_to_string, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest::header::CONTENT_LENGTH;
+use rss::extension::itunes::{ITunesChannelExtension, ITunesItemExtension};
+use rss::extension::Extension;
+use rss::Channel;
+
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::http::HttpClient;
+use crate::opts::Options;
+
+/// Prefix of the Podcasting 2.0 namespace elements.
+const PODCAST_NAMESPACE_PREFIX: &str = "podcast";
+/// Podcasting 2.0 namespace url.
+const PODCAST_NAMESPACE: &str = "https://podcastindex.org/namespace/1.0";
+/// Name of the article `extra` attribute holding media duration,
+/// Newsboat does not store enclosure durations so these have to
+/// be set by plugins or scripts, `itunes:duration` is omitted otherwise.
+const DURATION_ATTRIBUTE: &str = "duration";
+
+/// Resolver retrieving sizes of media enclosures via HEAD requests,
+/// sizes are cached between builds.
+pub struct EnclosureResolver<'a> {
+    client: &'a HttpClient,
+    /// Enclosure sizes retrieved during previous builds.
+    cached: HashMap<String, u64>,
+    /// Enclosure sizes of the articles processed during current build.
+    resolved: RefCell<HashMap<String, u64>>,
+}
+
+impl<'a> EnclosureResolver<'a> {
+    /// Initialize resolver, loading sizes cached at given path.
+    pub fn init(client: &'a HttpClient, path: &Path) -> EnclosureResolver<'a> {
+        let mut cached = HashMap::new();
+        if path.is_file() {
+            cached = read_to_string(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|raw| Ok(serde_json::from_str(&raw)?))
+                .unwrap_or_else(|e| {
+                    info!("Could not load enclosure cache: {}", e);
+                    HashMap::new()
+                });
+        }
+        return EnclosureResolver {
+            client,
+            cached,
+            resolved: RefCell::new(HashMap::new()),
+        };
+    }
+
+    /// Assign enclosure sizes to all the articles with
+    /// media enclosures.
+    pub fn resolve(&self, feeds: &Vec<Arc<RefCell<Feed>>>) {
+        info!("Resolving enclosure sizes");
+        for f in feeds {
+            for item in f.borrow_mut().truncated_iter() {
+                if !item.has_media_enclosure() || item.enc_length().is_some() {
+                    continue;
+                }
+                let url = item.enc_url().clone().unwrap();
+                if let Some(length) = self.get_length(&url) {
+                    item.set_enc_length(length);
+                }
+            }
+        }
+    }
+
+    /// Save sizes of the enclosures processed during current build.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut f = File::create(path)?;
+        f.write_all(serde_json::to_string(&self.resolved)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Retrieve size of the enclosure, either from cache or
+    /// `Content-Length` header of the HEAD response.
+    fn get_length(&self, url: &String) -> Option<u64> {
+        if let Some(length) = self.resolved.borrow().get(url) {
+            return Some(*length);
+        }
+        let length = match self.cached.get(url) {
+            Some(length) => *length,
+            None => {
+                let response = match self.client.head(url) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        warn!("Could not retrieve enclosure {}: {}", url, e);
+                        return None;
+                    }
+                };
+                response
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())?
+            }
+        };
+        self.resolved.borrow_mut().insert(url.clone(), length);
+        return Some(length);
+    }
+}
+
+/// Whether or not channel containing given articles should
+/// include podcast extensions.
+pub fn is_podcast_channel(opts: &Options, items: &[FeedItem]) -> bool {
+    return opts.podcast_channels
+        && items.iter().any(|i| i.has_media_enclosure());
+}
+
+/// Value of the iTunes explicit flag.
+fn explicit_flag(opts: &Options) -> String {
+    return opts.podcast_explicit.to_string();
+}
+
+/// Generate iTunes extension for the article, articles without
+/// media enclosures do not have any extension assigned.
+pub fn podcast_item_extension(
+    opts: &Options,
+    item: &FeedItem,
+) -> Option<ITunesItemExtension> {
+    if !item.has_media_enclosure() {
+        return None;
+    }
+    let image = match item.lead_image() {
+        Some(img) => Some(img.clone()),
+        None if !opts.podcast_image.is_empty() => {
+            Some(opts.podcast_image.clone())
+        }
+        None => None,
+    };
+    return Some(ITunesItemExtension {
+        author: Some(item.author().clone()).filter(|a| !a.is_empty()),
+        image,
+        duration: item.extra().get(DURATION_ATTRIBUTE).cloned(),
+        explicit: Some(explicit_flag(opts)),
+        summary: item.excerpt().clone(),
+        episode_type: Some(String::from("full")),
+        ..Default::default()
+    });
+}
+
+/// Add iTunes and Podcasting 2.0 channel level elements.
+pub fn set_podcast_channel_extensions(channel: &mut Channel, opts: &Options) {
+    channel.set_itunes_ext(ITunesChannelExtension {
+        author: Some(opts.title.clone()),
+        image: Some(opts.podcast_image.clone()).filter(|i| !i.is_empty()),
+        explicit: Some(explicit_flag(opts)),
+        r#type: Some(String::from("episodic")),
+        ..Default::default()
+    });
+    channel.namespaces.insert(
+        PODCAST_NAMESPACE_PREFIX.to_string(),
+        PODCAST_NAMESPACE.to_string(),
+    );
+    let medium = Extension {
+        name: format!("{}:medium", PODCAST_NAMESPACE_PREFIX),
+        value: Some(String::from("podcast")),
+        ..Default::default()
+    };
+    let mut elements = BTreeMap::new();
+    elements.insert(String::from("medium"), vec![medium]);
+    channel
+        .extensions
+        .insert(PODCAST_NAMESPACE_PREFIX.to_string(), elements);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn init_item(enc_mime: &str) -> FeedItem {
+        let mut item = FeedItem::new(
+            "Episode 1",
+            "http://podcast.com/1",
+            "http://podcast.com/rss",
+            "Host",
+            1733000000,
+            false,
+            "",
+            1,
+        );
+        item.set_enc_url("http://podcast.com/1.mp3".to_string());
+        item.set_enc_mime(enc_mime.to_string());
+        return item;
+    }
+
+    #[test]
+    fn test_generating_podcast_item_extension() {
+        let mut opts = Options::default();
+        opts.podcast_image = "http://podcast.com/cover.png".to_string();
+        let mut item = init_item("audio/mpeg");
+        item.set_extra("duration".to_string(), "01:02:03".to_string());
+        let ext = podcast_item_extension(&opts, &item).unwrap();
+        assert_eq!(Some("01:02:03"), ext.duration());
+        assert_eq!(Some("http://podcast.com/cover.png"), ext.image());
+        assert_eq!(Some("false"), ext.explicit());
+        assert_eq!(Some("Host"), ext.author());
+
+        let item = init_item("image/png");
+        assert!(podcast_item_extension(&opts, &item).is_none());
+        opts.podcast_channels = true;
+        assert!(!is_podcast_channel(&opts, &[item]));
+        assert!(is_podcast_channel(&opts, &[init_item("video/mp4")]));
+        opts.podcast_channels = false;
+        assert!(!is_podcast_channel(&opts, &[init_item("video/mp4")]));
+    }
+
+    #[test]
+    fn test_resolving_cached_enclosure_length() {
+        let path = std::env::temp_dir().join(format!(
+            "liveboat_enclosure_test_{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"http://podcast.com/1.mp3": 1234}"#).unwrap();
+        let opts = Options::default();
+        let client = HttpClient::init(&opts, true).unwrap();
+        let resolver = EnclosureResolver::init(&client, &path);
+        let f = Arc::new(RefCell::new(Feed::init(
+            "http://podcast.com/rss".to_string(),
+            "Podcast".to_string(),
+            "http://podcast.com".to_string(),
+        )));
+        f.borrow_mut().add_item(init_item("audio/mpeg"));
+        let mut missing = init_item("audio/mpeg");
+        missing.set_enc_url("http://podcast.com/2.mp3".to_string());
+        f.borrow_mut().add_item(missing);
+        let feeds = vec![f];
+        resolver.resolve(&feeds);
+
+        assert_eq!(Some(1234), feeds[0].borrow().items[0].enc_length());
+        assert_eq!(None, feeds[0].borrow().items[1].enc_length());
+        assert_eq!(1, client.skipped_requests().len());
+        resolver.save(&path).unwrap();
+        _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,225 @@
+/// This module contains sanitization policy applied to article
+/// content before it is written to the build directory.
+use std::borrow::Cow;
+
+use ammonia::Builder;
+use regex::{Captures, Regex};
+use url::Url;
+
+use crate::opts::Options;
+use crate::utils::get_html_attribute;
+
+/// Tags which are always stripped along with their content,
+/// these cannot be added to the list of allowed tags.
+const DISALLOWED_TAGS: [&str; 2] = ["script", "style"];
+/// Attributes allowed for embedded iframes.
+const IFRAME_ATTRIBUTES: [&str; 5] =
+    ["src", "width", "height", "title", "allowfullscreen"];
+
+/// Sanitizer removing potentially harmful markup from article content
+/// such as scripts, inline event handlers, tracking pixels and
+/// iframes pointing to non allowed hosts.
+pub struct Sanitizer<'a> {
+    builder: Builder<'a>,
+    remove_tracking_pixels: bool,
+    iframe_hosts: Vec<String>,
+}
+
+impl<'a> Sanitizer<'a> {
+    /// Initialize sanitizer using policy defined in options.
+    pub fn init(opts: &'a Options) -> Sanitizer<'a> {
+        let mut builder = Builder::default();
+        builder
+            .add_tags(
+                opts.sanitize_extra_tags
+                    .iter()
+                    .filter(|t| !DISALLOWED_TAGS.contains(&t.as_str())),
+            )
+            // Rel attribute is always overwritten for links.
+            .add_generic_attributes(
+                opts.sanitize_extra_attributes
+                    .iter()
+                    .filter(|a| *a != "rel"),
+            )
+            .link_rel(Some("noopener noreferrer"))
+            .set_tag_attribute_value("img", "loading", "lazy");
+        if !opts.sanitize_iframe_hosts.is_empty() {
+            let hosts = opts.sanitize_iframe_hosts.clone();
+            builder
+                .add_tags(&["iframe"])
+                .add_tag_attributes("iframe", &IFRAME_ATTRIBUTES)
+                .attribute_filter(move |el, attr, val| {
+                    if el == "iframe"
+                        && attr == "src"
+                        && !is_allowed_iframe_src(val, &hosts)
+                    {
+                        return None;
+                    }
+                    return Some(Cow::Borrowed(val));
+                });
+        }
+        return Sanitizer {
+            builder,
+            remove_tracking_pixels: opts.sanitize_remove_tracking_pixels,
+            iframe_hosts: opts.sanitize_iframe_hosts.clone(),
+        };
+    }
+
+    /// Sanitize html content.
+    pub fn sanitize(&self, content: &str) -> String {
+        if content.is_empty() {
+            return String::new();
+        }
+        let mut content =
+            remove_disallowed_iframes(content, &self.iframe_hosts);
+        if self.remove_tracking_pixels {
+            content = remove_tracking_pixels(&content);
+        }
+        return self.builder.clean(&content).to_string();
+    }
+}
+
+/// Check if iframe source points to one of allowed hosts,
+/// protocol relative sources are treated as https ones.
+fn is_allowed_iframe_src(src: &str, hosts: &[String]) -> bool {
+    let src = src.trim();
+    let url = match src.starts_with("//") {
+        true => Url::parse(&format!("https:{}", src)),
+        false => Url::parse(src),
+    };
+    let url = match url {
+        Ok(u) => u,
+        Err(_) => return false,
+    };
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return false;
+    }
+    return hosts.iter().any(|h| Some(h.as_str()) == url.host_str());
+}
+
+/// Remove iframe elements (along with fallback content) which
+/// point to hosts not present in the allowlist. Iframes which are
+/// not closed before the next iframe or the end of the content
+/// are closed right after the opening tag, so that the remaining
+/// content is not swallowed as iframe fallback.
+fn remove_disallowed_iframes(content: &str, hosts: &[String]) -> String {
+    let open_re = Regex::new(r#"(?i)<iframe\b([^>]*)>"#).unwrap();
+    let close_re = Regex::new(r#"(?i)</iframe\s*>"#).unwrap();
+    let tags: Vec<Captures> = open_re.captures_iter(content).collect();
+    let mut result = String::with_capacity(content.len());
+    let mut pos = 0;
+    for (i, tag) in tags.iter().enumerate() {
+        let open = tag.get(0).unwrap();
+        let next_open = tags
+            .get(i + 1)
+            .map_or(content.len(), |t| t.get(0).unwrap().start());
+        let close = close_re.find(&content[open.end()..next_open]);
+        let is_allowed = get_html_attribute(&tag[1], "src")
+            .is_some_and(|src| is_allowed_iframe_src(&src, hosts));
+        result.push_str(&content[pos..open.start()]);
+        match (is_allowed, close) {
+            (true, Some(c)) => {
+                result.push_str(&content[open.start()..open.end() + c.end()])
+            }
+            (true, None) => {
+                result.push_str(open.as_str());
+                result.push_str("</iframe>");
+            }
+            _ => (),
+        }
+        pos = match close {
+            Some(c) => open.end() + c.end(),
+            None => open.end(),
+        };
+    }
+    result.push_str(&content[pos..]);
+    return result;
+}
+
+/// Remove images with both dimensions of at most single pixel,
+/// which are commonly used for tracking purposes.
+fn remove_tracking_pixels(content: &str) -> String {
+    let img_re = Regex::new(r#"(?i)<img\b([^>]*)>"#).unwrap();
+    return img_re
+        .replace_all(content, |img: &Captures| {
+            let is_pixel = ["width", "height"].iter().all(|dim| {
+                get_html_attribute(&img[1], dim)
+                    .and_then(|v| {
+                        v.trim().trim_end_matches("px").parse::<f64>().ok()
+                    })
+                    .is_some_and(|v| v <= 1.0)
+            });
+            if is_pixel {
+                return String::new();
+            }
+            return img[0].to_string();
+        })
+        .to_string();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_sanitizing_content() {
+        let opts = Options::default();
+        let sanitizer = Sanitizer::init(&opts);
+        let content = r#"<p onclick="alert(1)">Text<script>alert(1)</script></p><a href="http://test.com" rel="opener">link</a><img src="http://test.com/a.png" loading="eager"><img src="http://track.com/p.gif" width="1" height="1"><iframe src="https://www.youtube-nocookie.com/embed/1" onload="x()"></iframe><iframe src="https://evil.com/embed"><p>fallback</p></iframe>"#;
+        assert_eq!(
+            r#"<p>Text</p><a href="http://test.com" rel="noopener noreferrer">link</a><img src="http://test.com/a.png" loading="lazy"><iframe src="https://www.youtube-nocookie.com/embed/1"></iframe>"#,
+            sanitizer.sanitize(content)
+        );
+    }
+
+    #[test]
+    fn test_sanitizing_content_with_custom_policy() {
+        let mut opts = Options::default();
+        opts.sanitize_extra_tags =
+            vec!["video".to_string(), "script".to_string()];
+        opts.sanitize_extra_attributes =
+            vec!["data-id".to_string(), "rel".to_string()];
+        opts.sanitize_iframe_hosts = vec![];
+        opts.sanitize_remove_tracking_pixels = false;
+        let sanitizer = Sanitizer::init(&opts);
+        let content = r#"<video data-id="1"></video><script>x()</script><img src="p.gif" width="1" height="1"><iframe src="https://www.youtube-nocookie.com/embed/1"></iframe>"#;
+        assert_eq!(
+            r#"<video data-id="1"></video><img src="p.gif" width="1" height="1" loading="lazy">"#,
+            sanitizer.sanitize(content)
+        );
+    }
+
+    #[test]
+    fn test_removing_disallowed_iframes() {
+        let hosts = vec!["www.youtube-nocookie.com".to_string()];
+        let content = r#"<p>Intro</p><iframe src="https://evil.com/embed"><p>Rest of the article</p>"#;
+        assert_eq!(
+            "<p>Intro</p><p>Rest of the article</p>",
+            remove_disallowed_iframes(content, &hosts)
+        );
+        let content = r#"<iframe src="//www.youtube-nocookie.com/embed/1"><p>Text</p><iframe src="https://evil.com/embed">fallback</iframe><p>End</p>"#;
+        assert_eq!(
+            r#"<iframe src="//www.youtube-nocookie.com/embed/1"></iframe><p>Text</p><p>End</p>"#,
+            remove_disallowed_iframes(content, &hosts)
+        );
+
+        let opts = Options::default();
+        let sanitizer = Sanitizer::init(&opts);
+        assert_eq!(
+            "<p>Intro</p><p>Rest of the article</p>",
+            sanitizer.sanitize(
+                r#"<p>Intro</p><iframe src="https://evil.com/embed"><p>Rest of the article</p>"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_removing_tracking_pixels() {
+        let content = r#"<img src="a.png" width="1px" height='0'><img src="b.png" width=1><img src="c.png" width="100" height="1">"#;
+        assert_eq!(
+            r#"<img src="b.png" width=1><img src="c.png" width="100" height="1">"#,
+            remove_tracking_pixels(content)
+        );
+    }
+}
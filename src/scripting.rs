@@ -0,0 +1,370 @@
+/// This module contains logic related to running user defined Rhai
+/// scripts, which can transform or drop articles at various stages
+/// of the build.
+use log::{info, warn};
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+use crate::content::strip_html_tags;
+use crate::errors::ScriptError;
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+
+/// Maximum number of operations single script function call can
+/// perform, prevents infinite loops from stalling the build.
+const MAX_SCRIPT_OPERATIONS: u64 = 1_000_000;
+
+/// Build stage at which script functions are executed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptHook {
+    /// Articles have been loaded from the Newsboat db.
+    Load,
+    /// Article content has been processed.
+    Process,
+    /// Articles are about to be rendered.
+    Render,
+}
+
+impl ScriptHook {
+    /// Name of the script function called for given stage.
+    fn fn_name(&self) -> &'static str {
+        match self {
+            ScriptHook::Load => "on_load",
+            ScriptHook::Process => "on_process",
+            ScriptHook::Render => "on_render",
+        }
+    }
+}
+
+/// Runner executing user scripts for each of the articles.
+pub struct ScriptRunner {
+    engine: Engine,
+    scripts: Vec<(String, AST)>,
+}
+
+impl ScriptRunner {
+    /// Initialize runner compiling all the scripts at given paths.
+    pub fn init(paths: &Vec<PathBuf>) -> Result<ScriptRunner> {
+        let mut sources = Vec::new();
+        for path in paths {
+            let source = fs::read_to_string(path).map_err(|e| {
+                ScriptError::Load(path.display().to_string(), e.to_string())
+            })?;
+            sources.push((path.display().to_string(), source));
+        }
+        return ScriptRunner::init_with_sources(sources);
+    }
+
+    /// Initialize runner compiling scripts from (name, source) pairs.
+    pub fn init_with_sources(
+        sources: Vec<(String, String)>,
+    ) -> Result<ScriptRunner> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        let mut scripts = Vec::new();
+        for (name, source) in sources {
+            let ast = engine.compile(&source).map_err(|e| {
+                ScriptError::Compile(name.clone(), e.to_string())
+            })?;
+            scripts.push((name, ast));
+        }
+        return Ok(ScriptRunner { engine, scripts });
+    }
+
+    /// Run script functions defined for given stage against all
+    /// the articles, removing those marked as dropped.
+    pub fn run(&self, hook: ScriptHook, feeds: &Vec<Arc<RefCell<Feed>>>) {
+        let fn_name = hook.fn_name();
+        let scripts: Vec<&(String, AST)> = self
+            .scripts
+            .iter()
+            .filter(|(_, ast)| ast.iter_functions().any(|f| f.name == fn_name))
+            .collect();
+        if scripts.is_empty() {
+            return;
+        }
+        info!("Running {} script functions", fn_name);
+        for f in feeds {
+            let feed = feed_to_map(&f.borrow());
+            let mut dropped = Vec::new();
+            for item in f.borrow_mut().items_mut() {
+                for (name, ast) in &scripts {
+                    match self.call(ast, fn_name, item, &feed) {
+                        Ok(true) => continue,
+                        Ok(false) => {
+                            info!("Script {} dropped {}", name, item);
+                            dropped.push(item.guid());
+                            break;
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Script error in {} for {}: {}",
+                                name, item, e
+                            )
+                        }
+                    }
+                }
+            }
+            if !dropped.is_empty() {
+                f.borrow_mut()
+                    .items
+                    .retain(|i| !dropped.contains(&i.guid()));
+            }
+        }
+    }
+
+    /// Call script function with `this` bound to the article,
+    /// returns false if article should be dropped. Article is left
+    /// intact if script fails.
+    fn call(
+        &self,
+        ast: &AST,
+        fn_name: &str,
+        item: &mut FeedItem,
+        feed: &Map,
+    ) -> Result<bool> {
+        let mut this = Dynamic::from_map(item_to_map(item));
+        let options = CallFnOptions::new()
+            .bind_this_ptr(&mut this)
+            .eval_ast(false);
+        _ = self
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                ast,
+                fn_name,
+                (Dynamic::from_map(feed.clone()),),
+            )
+            .map_err(|e| {
+                ScriptError::Runtime(fn_name.to_string(), e.to_string())
+            })?;
+        let Some(map) = this.try_cast::<Map>() else {
+            return Err(ScriptError::Runtime(
+                fn_name.to_string(),
+                "`this` is no longer an object map".to_string(),
+            )
+            .into());
+        };
+        if map.get("drop").is_some_and(|d| d.as_bool() == Ok(true)) {
+            return Ok(false);
+        }
+        apply_map(item, map);
+        return Ok(true);
+    }
+}
+
+/// Convert article into object map exposed to the scripts.
+fn item_to_map(item: &FeedItem) -> Map {
+    let mut map = Map::new();
+    let opt = |v: &Option<String>| match v {
+        Some(s) => Dynamic::from(s.clone()),
+        None => Dynamic::UNIT,
+    };
+    map.insert("title".into(), item.title().clone().into());
+    map.insert("url".into(), item.url().clone().into());
+    map.insert("original_url".into(), item.original_url().clone().into());
+    map.insert("feed_url".into(), item.feed_url().clone().into());
+    map.insert("author".into(), item.author().clone().into());
+    map.insert("date".into(), item.date().into());
+    map.insert("unread".into(), item.unread().into());
+    map.insert("content".into(), item.content().clone().into());
+    map.insert("text".into(), opt(item.text()));
+    map.insert("comments_url".into(), opt(item.comments_url()));
+    map.insert("enclosure_url".into(), opt(item.enc_url()));
+    map.insert("enclosure_mime".into(), opt(item.enc_mime()));
+    map.insert(
+        "score".into(),
+        item.score().map(Dynamic::from).unwrap_or(Dynamic::UNIT),
+    );
+    map.insert(
+        "comment_count".into(),
+        item.comment_count()
+            .map(Dynamic::from)
+            .unwrap_or(Dynamic::UNIT),
+    );
+    let extra: Map = item
+        .extra()
+        .iter()
+        .map(|(k, v)| (k.into(), v.clone().into()))
+        .collect();
+    map.insert("extra".into(), extra.into());
+    let tags: Array = item.tags().iter().map(|t| t.clone().into()).collect();
+    map.insert("tags".into(), tags.into());
+    map.insert("drop".into(), false.into());
+    return map;
+}
+
+/// Convert feed into object map passed to script functions.
+fn feed_to_map(feed: &Feed) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), feed.id().clone().into());
+    map.insert("title".into(), feed.display_title().clone().into());
+    map.insert("url".into(), feed.url().clone().into());
+    map.insert("link".into(), feed.feedlink().clone().into());
+    let tags: Array = feed.tags.iter().map(|t| t.clone().into()).collect();
+    map.insert("tags".into(), tags.into());
+    return map;
+}
+
+/// Update article with fields modified by the script.
+fn apply_map(item: &mut FeedItem, mut map: Map) {
+    let mut take_string = |key: &str| {
+        return map
+            .remove(key)
+            .and_then(|v| v.into_string().ok())
+            .filter(|v| !v.is_empty());
+    };
+    if let Some(title) = take_string("title") {
+        item.set_title(title);
+    }
+    if let Some(url) = take_string("url") {
+        item.set_url(url);
+    }
+    if let Some(author) = take_string("author") {
+        item.set_author(author);
+    }
+    if let Some(comments_url) = take_string("comments_url") {
+        item.set_comments_url(comments_url);
+    }
+    if let Some(content) = take_string("content") {
+        if &content != item.content() {
+            let text = strip_html_tags(&content);
+            item.set_content_length(text.len());
+            item.set_text(text);
+            item.set_content(content);
+        }
+    }
+    if let Some(tags) = map.remove("tags").and_then(|t| t.try_cast::<Array>()) {
+        for tag in tags.into_iter().filter_map(|t| t.into_string().ok()) {
+            item.add_tag(tag);
+        }
+    }
+    if let Some(extra) = map.remove("extra").and_then(|e| e.try_cast::<Map>()) {
+        for (key, value) in extra {
+            if value.is_unit() {
+                continue;
+            }
+            let value = match value.clone().into_string() {
+                Ok(s) => s,
+                Err(_) => value.to_string(),
+            };
+            item.set_extra(key.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use libnewsboat::matchable::Matchable;
+
+    fn init_feeds() -> Vec<Arc<RefCell<Feed>>> {
+        let mut f = Feed::init(
+            "https://example.com/rss".to_string(),
+            "Example".to_string(),
+            "https://example.com".to_string(),
+        );
+        f.tags = vec!["tech".to_string()];
+        for (guid, title, url) in [
+            (1, "[Sponsored] Buy now", "https://example.com/ad"),
+            (2, "Regular post", "https://nitter.net/user/status/1"),
+        ] {
+            f.add_item(FeedItem::new(
+                title,
+                url,
+                "https://example.com/rss",
+                "",
+                123456,
+                false,
+                "<p>Content</p>",
+                guid,
+            ));
+        }
+        return vec![Arc::new(RefCell::new(f))];
+    }
+
+    fn init_runner(source: &str) -> ScriptRunner {
+        return ScriptRunner::init_with_sources(vec![(
+            "test.rhai".to_string(),
+            source.to_string(),
+        )])
+        .unwrap();
+    }
+
+    #[test]
+    fn test_running_scripts() {
+        let runner = init_runner(
+            r#"
+            fn on_load(feed) {
+                if this.title.starts_with("[Sponsored]") {
+                    this.drop = true;
+                }
+                this.url.replace("nitter.net", "x.com");
+                this.extra.feed_tag = feed.tags[0];
+                this.extra.year = 2024;
+                this.tags.push("social");
+            }
+            "#,
+        );
+        let feeds = init_feeds();
+        runner.run(ScriptHook::Process, &feeds);
+        assert_eq!(2, feeds[0].borrow().items.len());
+
+        runner.run(ScriptHook::Load, &feeds);
+        let f = feeds[0].borrow();
+        assert_eq!(1, f.items.len());
+        assert_eq!("https://x.com/user/status/1", f.items[0].url());
+        assert_eq!(
+            Some("tech".to_string()),
+            f.items[0].attribute_value("feed_tag")
+        );
+        assert_eq!(&vec!["social"], f.items[0].tags());
+        assert_eq!(
+            Some("2024".to_string()),
+            f.items[0].extra().get("year").cloned()
+        );
+    }
+
+    #[test]
+    fn test_isolating_script_errors() {
+        let runner = init_runner(
+            r#"
+            fn on_render(feed) {
+                if this.url.contains("nitter") {
+                    this.title = "Changed";
+                    throw "failure";
+                }
+                this.content = "<b>New</b>";
+            }
+            "#,
+        );
+        let feeds = init_feeds();
+        runner.run(ScriptHook::Render, &feeds);
+        let f = feeds[0].borrow();
+        assert_eq!("<b>New</b>", f.items[0].content());
+        assert_eq!(&Some("New".to_string()), f.items[0].text());
+        assert_eq!("Regular post", f.items[1].title());
+        assert_eq!("<p>Content</p>", f.items[1].content());
+    }
+
+    #[test]
+    fn test_limiting_script_operations() {
+        let runner = init_runner("fn on_load(feed) { loop { } }");
+        let feeds = init_feeds();
+        runner.run(ScriptHook::Load, &feeds);
+        assert_eq!(2, feeds[0].borrow().items.len());
+
+        let res = ScriptRunner::init_with_sources(vec![(
+            "invalid.rhai".to_string(),
+            "fn on_load(feed) {".to_string(),
+        )]);
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,211 @@
+/// This module contains logic related to assigning article level
+/// tags based on the rules defined in options.
+use log::{info, warn};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use anyhow::Result;
+use libnewsboat::matcher::Matcher;
+use regex::Regex;
+
+use crate::errors::TaggingError;
+use crate::feed::Feed;
+use crate::feed_item::FeedItem;
+use crate::opts::{Options, TagRule};
+
+/// Tagging rule with compiled filter and patterns.
+struct CompiledRule<'a> {
+    tags: &'a Vec<String>,
+    filter: Option<Matcher>,
+    title_pattern: Option<Regex>,
+    content_pattern: Option<Regex>,
+}
+
+/// Tagger assigning tags to articles matching the rules.
+pub struct Tagger<'a> {
+    rules: Vec<CompiledRule<'a>>,
+}
+
+impl<'a> Tagger<'a> {
+    /// Initialize tagger, compiling rules defined in options.
+    pub fn init(opts: &'a Options) -> Result<Tagger<'a>> {
+        let mut rules = Vec::new();
+        for rule in &opts.tag_rules {
+            rules.push(compile_rule(rule)?);
+        }
+        return Ok(Tagger { rules });
+    }
+
+    /// Assign tags to all the articles.
+    pub fn apply(&self, feeds: &Vec<Arc<RefCell<Feed>>>) {
+        info!("Assigning article tags");
+        for f in feeds {
+            // Matching is done on immutable borrow first since matcher
+            // accesses feed attributes via article feed pointer.
+            let tags: Vec<Vec<&String>> =
+                f.borrow().items.iter().map(|i| self.get_tags(i)).collect();
+            for (item, tags) in f.borrow_mut().items_mut().zip(tags) {
+                for tag in tags {
+                    item.add_tag(tag.clone());
+                }
+            }
+        }
+    }
+
+    /// Retrieve tags of all the rules matching the article.
+    fn get_tags(&self, item: &FeedItem) -> Vec<&String> {
+        return self
+            .rules
+            .iter()
+            .filter(|r| rule_matches(r, item))
+            .flat_map(|r| r.tags.iter())
+            .collect();
+    }
+}
+
+/// Compile filter expression and patterns of the rule.
+fn compile_rule(rule: &TagRule) -> Result<CompiledRule<'_>> {
+    if rule.filter.is_none()
+        && rule.title_pattern.is_none()
+        && rule.content_pattern.is_none()
+    {
+        return Err(TaggingError::MissingConditions(rule.tags.clone()).into());
+    }
+    let filter = match &rule.filter {
+        Some(f) => Some(
+            Matcher::parse(f)
+                .map_err(|e| TaggingError::InvalidFilter(f.clone(), e))?,
+        ),
+        None => None,
+    };
+    let compile_pattern = |pattern: &Option<String>| match pattern {
+        Some(p) => Regex::new(p).map(Some).map_err(|e| {
+            TaggingError::InvalidPattern(p.clone(), e.to_string())
+        }),
+        None => Ok(None),
+    };
+    return Ok(CompiledRule {
+        tags: &rule.tags,
+        filter,
+        title_pattern: compile_pattern(&rule.title_pattern)?,
+        content_pattern: compile_pattern(&rule.content_pattern)?,
+    });
+}
+
+/// Check if article matches all the conditions defined by the rule.
+/// Content pattern is matched against extracted article text
+/// if available.
+fn rule_matches(rule: &CompiledRule, item: &FeedItem) -> bool {
+    if let Some(filter) = &rule.filter {
+        match filter.matches(item) {
+            Ok(true) => (),
+            Ok(false) => return false,
+            Err(e) => {
+                warn!("Matcher error: {:?}", e);
+                return false;
+            }
+        }
+    }
+    if let Some(p) = &rule.title_pattern {
+        if !p.is_match(item.title()) {
+            return false;
+        }
+    }
+    if let Some(p) = &rule.content_pattern {
+        let content = item.text().as_ref().unwrap_or(item.content());
+        if !p.is_match(content) {
+            return false;
+        }
+    }
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use libnewsboat::matchable::Matchable;
+
+    fn init_rule(
+        tags: &[&str],
+        filter: Option<&str>,
+        title_pattern: Option<&str>,
+        content_pattern: Option<&str>,
+    ) -> TagRule {
+        return TagRule {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            filter: filter.map(|f| f.to_string()),
+            title_pattern: title_pattern.map(|p| p.to_string()),
+            content_pattern: content_pattern.map(|p| p.to_string()),
+        };
+    }
+
+    fn init_feeds() -> Vec<Arc<RefCell<Feed>>> {
+        let f = Arc::new(RefCell::new(Feed::init(
+            "https://blog.com/rss".to_string(),
+            "Blog".to_string(),
+            "https://blog.com".to_string(),
+        )));
+        for (guid, title, content) in [
+            (1, "Announcing Rust 1.80", "<p>New release</p>"),
+            (2, "Weekly links", "<p>Includes Rust and Go</p>"),
+            (3, "Cooking", "<p>Pasta</p>"),
+        ] {
+            let mut item = FeedItem::new(
+                title,
+                "https://blog.com/post",
+                "https://blog.com/rss",
+                "",
+                123456,
+                false,
+                content,
+                guid,
+            );
+            item.set_ptr(Arc::clone(&f));
+            f.borrow_mut().add_item(item);
+        }
+        return vec![f];
+    }
+
+    #[test]
+    fn test_tagging_articles() {
+        let mut opts = Options::default();
+        opts.tag_rules = vec![
+            init_rule(&["rust", "release"], None, Some("(?i)rust \\d"), None),
+            init_rule(&["rust"], None, None, Some("\\bRust\\b")),
+            init_rule(
+                &["blog"],
+                Some("rssurl =~ \"blog.com\" and title !~ \"Cooking\""),
+                None,
+                None,
+            ),
+        ];
+        let tagger = Tagger::init(&opts).unwrap();
+        let feeds = init_feeds();
+        tagger.apply(&feeds);
+
+        let f = feeds[0].borrow();
+        assert_eq!(&vec!["rust", "release", "blog"], f.items[0].tags());
+        assert_eq!(&vec!["rust", "blog"], f.items[1].tags());
+        assert!(f.items[2].tags().is_empty());
+        assert_eq!(
+            Some("rust blog".to_string()),
+            f.items[1].attribute_value("article_tags")
+        );
+        let rss_item = f.items[0].clone().to_rss_item(false);
+        let categories: Vec<&str> =
+            rss_item.categories().iter().map(|c| c.name()).collect();
+        assert_eq!(vec!["rust", "release", "blog"], categories);
+    }
+
+    #[test]
+    fn test_validating_tag_rules() {
+        let mut opts = Options::default();
+        opts.tag_rules = vec![init_rule(&["a"], None, None, None)];
+        assert!(Tagger::init(&opts).is_err());
+        opts.tag_rules = vec![init_rule(&["a"], Some("title ="), None, None)];
+        assert!(Tagger::init(&opts).is_err());
+        opts.tag_rules = vec![init_rule(&["a"], None, Some("[a"), None)];
+        assert!(Tagger::init(&opts).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 
+use crate::builders::utils::{get_tag_feeds, ChannelFormat};
 use crate::errors::FilesystemError;
 use crate::feed::Feed;
 use crate::opts::Options;
@@ -56,6 +57,21 @@ pub trait Context {
     #[allow(dead_code)]
     fn options(&self) -> &Options;
     fn build_time(&self) -> u64;
+    fn alternate_links(&self) -> &Vec<AlternateLink>;
+    fn skipped_requests(&self) -> &Vec<String>;
+}
+
+/// Link to alternate representation of the page
+/// (eg. RSS channel) rendered as `<link rel="alternate">`.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct AlternateLink {
+    /// Title of the linked document.
+    title: String,
+    /// Path to the document relative to the site root.
+    href: String,
+    /// Mime type of the linked document.
+    #[serde(rename = "type")]
+    mime_type: String,
 }
 
 /// Representation of default context to be passed
@@ -72,6 +88,10 @@ pub struct SimpleContext<'a> {
     template_settings: &'a HashMap<String, String>,
     /// Version of template as defined in template config.
     template_version: String,
+    /// Links to channels generated for the page.
+    alternate_links: Vec<AlternateLink>,
+    /// Urls of the network requests skipped in offline mode.
+    skipped_requests: Vec<String>,
 }
 
 impl<'a> Context for SimpleContext<'a> {
@@ -84,6 +104,12 @@ impl<'a> Context for SimpleContext<'a> {
     fn build_time(&self) -> u64 {
         return self.build_time;
     }
+    fn alternate_links(&self) -> &Vec<AlternateLink> {
+        return &self.alternate_links;
+    }
+    fn skipped_requests(&self) -> &Vec<String> {
+        return &self.skipped_requests;
+    }
 }
 
 impl<'a> SimpleContext<'a> {
@@ -94,6 +120,7 @@ impl<'a> SimpleContext<'a> {
         options: &'a Options,
         template_settings: &'a HashMap<String, String>,
         template_version: String,
+        skipped_requests: Vec<String>,
     ) -> SimpleContext<'a> {
         let mut feeds = Vec::new();
         for f in url_feeds {
@@ -113,14 +140,56 @@ impl<'a> SimpleContext<'a> {
         let build_time = since_the_epoch.as_secs();
 
         feeds.sort_by(|a, b| a.order_idx().cmp(b.order_idx()));
+        let alternate_links = get_alternate_links(&feeds, options);
         SimpleContext {
             feeds,
             options,
             build_time,
             template_settings,
             template_version,
+            alternate_links,
+            skipped_requests,
+        }
+    }
+}
+
+/// Retrieve links to all the channels generated for the page.
+fn get_alternate_links(feeds: &[Feed], opts: &Options) -> Vec<AlternateLink> {
+    let mut formats = vec![ChannelFormat::Rss];
+    if opts.atom_feeds {
+        formats.push(ChannelFormat::Atom);
+    }
+    if opts.json_feeds {
+        formats.push(ChannelFormat::JsonFeed);
+    }
+    let site_path = opts.site_path.trim_end_matches('/');
+    let mut links = Vec::new();
+    for format in &formats {
+        links.push(AlternateLink {
+            title: opts.title.clone(),
+            href: format!("{}/{}", site_path, format.site_channel_path()),
+            mime_type: format.mime_type().to_string(),
+        });
+    }
+    let mut channel_feeds: Vec<&Feed> = feeds
+        .iter()
+        .filter(|f| f.is_query_feed() && !f.is_empty() && !f.is_hidden())
+        .collect();
+    let tag_feeds = match opts.tag_channels {
+        true => get_tag_feeds(feeds),
+        false => Vec::new(),
+    };
+    channel_feeds.extend(tag_feeds.iter());
+    for f in channel_feeds {
+        for format in &formats {
+            links.push(AlternateLink {
+                title: f.display_title().clone(),
+                href: format!("{}/{}", site_path, format.feed_channel_path(f)),
+                mime_type: format.mime_type().to_string(),
+            });
         }
     }
+    return links;
 }
 
 impl fmt::Display for SimpleContext<'_> {
@@ -205,6 +274,7 @@ mod tests {
             &opts,
             &settings,
             String::from("1.0.0"),
+            Vec::new(),
         );
 
         assert_eq!(4, ctx.feeds.len());
@@ -217,5 +287,28 @@ mod tests {
         assert!(titles.contains(&"Url feed2".to_string()));
         assert!(titles.contains(&"Url feed3".to_string()));
         assert!(titles.contains(&"Query feed2".to_string()));
+        assert_eq!(6, ctx.alternate_links.len());
+        assert_eq!(
+            AlternateLink {
+                title: "Query feed2".to_string(),
+                href: "/channels/MCajjVFkGciw3kD.atom.xml".to_string(),
+                mime_type: "application/atom+xml".to_string(),
+            },
+            ctx.alternate_links[4]
+        );
+    }
+
+    #[test]
+    fn test_generating_alternate_links_for_site_path() {
+        let mut opts = Options::default();
+        opts.json_feeds = false;
+        for site_path in ["/news", "/news/"] {
+            opts.site_path = site_path.to_string();
+            let hrefs: Vec<String> = get_alternate_links(&Vec::new(), &opts)
+                .into_iter()
+                .map(|l| l.href)
+                .collect();
+            assert_eq!(vec!["/news/rss.xml", "/news/atom.xml"], hrefs);
+        }
     }
 }
@@ -3,9 +3,7 @@ use log::info;
 use rand::{distributions::Alphanumeric, Rng};
 use std::cmp::Ordering;
 use std::fs;
-use std::fs::File;
 use std::io;
-use std::io::copy as ioCopy;
 use std::path::Path;
 
 #[cfg(not(test))]
@@ -16,6 +14,7 @@ use chrono::Utc;
 
 use anyhow::{anyhow, Result};
 use env_logger::Env;
+use regex::Regex;
 
 /// Representation of Versioning used by liveboat,
 /// conforming to <major>.<minor>.<patch> format.
@@ -94,15 +93,6 @@ pub fn generate_random_string(len: usize) -> String {
         .collect();
 }
 
-/// Fetch file from url saving it under path specified
-pub fn download_file(url: &str, f_name: &Path) -> Result<()> {
-    let mut response = reqwest::blocking::get(url)?;
-    let mut file = File::create(f_name)?;
-    ioCopy(&mut response, &mut file)?;
-
-    Ok(())
-}
-
 /// Cleanup temp dir
 pub fn tidy_up(tmp_dir: &Path) {
     _ = fs::remove_dir_all(tmp_dir);
@@ -127,6 +117,21 @@ pub fn copy_all(
     Ok(())
 }
 
+/// Retrieve value of the attribute from raw tag attribute string.
+pub fn get_html_attribute(attrs: &str, name: &str) -> Option<String> {
+    let attr_re = Regex::new(&format!(
+        r#"(?i)(?:^|\s){}\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#,
+        name
+    ))
+    .unwrap();
+    let caps = attr_re.captures(attrs)?;
+    return caps
+        .get(1)
+        .or(caps.get(2))
+        .or(caps.get(3))
+        .map(|m| m.as_str().to_string());
+}
+
 #[cfg(test)]
 mod tests {
 